@@ -4,6 +4,7 @@ local fn = vim.fn
 local nvim = {}
 
 nvim.cache_dir = fn.stdpath 'cache'
+nvim.state_dir = fn.stdpath 'state'
 
 ---@param name string
 ---@return any
@@ -35,9 +36,11 @@ function nvim.create_autocmd(event, pattern, callback, once)
     })
 end
 
+--- Unlike `nvim_del_autocmd()`, this doesn't fail if the autocommand has already
+--- been removed (e.g. a `once` autocommand which was executed).
 ---@param id integer
 function nvim.del_autocmd(id)
-    api.nvim_del_autocmd(id)
+    pcall(api.nvim_del_autocmd, id)
 end
 
 ---@param event string|string[]
@@ -48,15 +51,169 @@ function nvim.get_autocmds(event)
     }
 end
 
+--- If {buffer} is given, the autocommands are executed in the context of it.
 ---@param event string|string[]
 ---@param group integer?
+---@param buffer integer?
+---@param pattern string?
 ---@param data any
-function nvim.exec_autocmds(event, group, data)
-    api.nvim_exec_autocmds(event, {
-        data = data,
-        group = group,
-        modeline = false,
-    })
+function nvim.exec_autocmds(event, group, buffer, pattern, data)
+    local function exec()
+        api.nvim_exec_autocmds(event, {
+            buffer = buffer,
+            data = data,
+            group = group,
+            modeline = false,
+            pattern = pattern,
+        })
+    end
+    if buffer then
+        api.nvim_buf_call(buffer, exec)
+    else
+        exec()
+    end
+end
+
+---@return boolean
+function nvim.vim_did_enter()
+    return vim.v.vim_did_enter == 1
+end
+
+---@return boolean
+function nvim.has_ui()
+    return #api.nvim_list_uis() > 0
+end
+
+--- Return `true` if the user has typed keys that are not processed yet.
+---@return boolean
+function nvim.input_pending()
+    return fn.getchar(1) ~= 0
+end
+
+---@param callback fun()
+function nvim.schedule(callback)
+    vim.schedule(callback)
+end
+
+---@param callback fun()
+---@param timeout integer
+function nvim.defer(callback, timeout)
+    vim.defer_fn(callback, timeout)
+end
+
+--- Return the loaded buffers.
+---@return integer[]
+function nvim.list_bufs()
+    return vim.tbl_filter(api.nvim_buf_is_loaded, api.nvim_list_bufs())
+end
+
+---@param buf integer
+---@param name string
+---@return any
+function nvim.get_buf_opt(buf, name)
+    return api.nvim_get_option_value(name, { buf = buf })
+end
+
+---@param name string
+---@param command fun(ev: table)
+---@param opts table
+function nvim.create_user_command(name, command, opts)
+    api.nvim_create_user_command(name, command, opts)
+end
+
+---@param name string
+function nvim.del_user_command(name)
+    api.nvim_del_user_command(name)
+end
+
+---@param cmd table
+function nvim.cmd(cmd)
+    api.nvim_cmd(cmd, {})
+end
+
+---@param pat string
+---@param type string
+---@return string[]
+function nvim.get_completion(pat, type)
+    return fn.getcompletion(pat, type)
+end
+
+---@param mode string
+---@param lhs string
+---@param rhs fun(): string?
+---@param opts table
+function nvim.set_keymap(mode, lhs, rhs, opts)
+    vim.keymap.set(mode, lhs, rhs, opts)
+end
+
+--- Unlike `vim.keymap.del()`, this doesn't fail if the mapping has already been
+--- removed (e.g. the buffer was wiped out).
+---@param mode string
+---@param lhs string
+---@param buffer integer?
+function nvim.del_keymap(mode, lhs, buffer)
+    pcall(vim.keymap.del, mode, lhs, { buffer = buffer })
+end
+
+---@return integer
+function nvim.get_current_buf()
+    return api.nvim_get_current_buf()
+end
+
+---@param keys string
+---@param mode string
+function nvim.feedkeys(keys, mode)
+    api.nvim_feedkeys(api.nvim_replace_termcodes(keys, true, true, true), mode, false)
+end
+
+---@param name string
+---@return any
+function nvim.get_vvar(name)
+    return vim.v[name]
+end
+
+---@return string
+function nvim.getcwd()
+    return fn.getcwd()
+end
+
+--- Return `true` if some files in {dir} match {pattern}.
+---@param dir string
+---@param pattern string
+---@return boolean
+function nvim.glob_exists(dir, pattern)
+    local path = dir:gsub(',', '\\,')
+    return #fn.globpath(path, pattern, true, true) > 0
+end
+
+--- Return `nil` if the client has already been stopped.
+---@param id integer
+---@return vim.lsp.Client?
+function nvim.get_lsp_client(id)
+    return vim.lsp.get_client_by_id(id)
+end
+
+--- Run `:filetype detect` in the context of {buf} if the detection is enabled.
+---@param buf integer
+function nvim.detect_filetype(buf)
+    if fn.exists '#filetypedetect#BufRead' == 0 then
+        return
+    end
+    api.nvim_buf_call(buf, function()
+        vim.cmd 'filetype detect'
+    end)
+end
+
+--- Generate `{dir}/tags`.
+---@param dir string
+function nvim.helptags(dir)
+    pcall(vim.cmd.helptags, fn.fnameescape(dir))
+end
+
+---@param msg string
+---@param level string
+function nvim.notify(msg, level)
+    vim.notify(msg, vim.log.levels[level])
 end
 
 return nvim
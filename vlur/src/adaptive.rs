@@ -0,0 +1,167 @@
+//! `lazy = 'adaptive'`: the plugin is loaded at startup while it is cheap,
+//! and deferred to `User VeryLazy` once its sourcing time exceeds the budget.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use hashbrown::HashMap;
+use log::{error, trace};
+use mlua::prelude::*;
+
+/// Used if `adaptive_budget` is not given in the config.
+pub const DEFAULT_BUDGET_MS: f64 = 5.0;
+
+/// Sourcing time of the plugins, keyed by the path to the plugin's directory.
+///
+/// It is stored in `{state_dir}/load_time` as lines of `{microseconds}\t{path}`.
+#[derive(Default)]
+pub struct LoadTimes(HashMap<String, Duration>);
+
+impl LoadTimes {
+    pub fn read(path: &Path) -> Self {
+        let Ok(content) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        Self::parse(&content)
+    }
+
+    pub fn write(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.format())
+    }
+
+    fn parse(content: &str) -> Self {
+        let times = content
+            .lines()
+            .filter_map(|line| {
+                let (micros, dir) = line.split_once('\t')?;
+                let micros = micros.parse().ok()?;
+                Some((dir.to_string(), Duration::from_micros(micros)))
+            })
+            .collect();
+        Self(times)
+    }
+
+    fn format(&self) -> String {
+        let mut entries = self.0.iter().collect::<Vec<_>>();
+        entries.sort_by_key(|(dir, _)| dir.as_str());
+        entries
+            .into_iter()
+            .map(|(dir, time)| format!("{}\t{dir}\n", time.as_micros()))
+            .collect()
+    }
+
+    #[inline]
+    pub fn get(&self, dir: &str) -> Option<Duration> {
+        self.0.get(dir).copied()
+    }
+
+    /// Start measuring the plugin again.
+    #[inline]
+    pub fn reset(&mut self, dir: &str) {
+        self.0.insert(dir.to_string(), Duration::ZERO);
+    }
+
+    #[inline]
+    pub fn add(&mut self, dir: &str, time: Duration) {
+        *self.0.entry(dir.to_string()).or_default() += time;
+    }
+}
+
+/// Wrap the loader of a deferred plugin to measure it again,
+/// so that it is loaded at startup once it becomes cheap enough.
+pub fn timed_loader<'lua>(
+    lua: &'lua Lua,
+    plugin_loader: LuaFunction<'lua>,
+    file: PathBuf,
+    dir: String,
+) -> LuaResult<LuaFunction<'lua>> {
+    let loader = move |_lua, plugin_loader: LuaFunction| {
+        let start = Instant::now();
+        plugin_loader.call::<_, ()>(())?;
+        let time = start.elapsed();
+
+        let mut load_times = LoadTimes::read(&file);
+        load_times.reset(&dir);
+        load_times.add(&dir, time);
+        if load_times.write(&file).is_err() {
+            error!("failed to write the load time");
+        }
+
+        Ok(())
+    };
+
+    lua.create_function(loader)?.bind(plugin_loader)
+}
+
+/// Record that the plugin is deferred, for [`deferred()`].
+pub fn defer(lua: &Lua, plugin_id: &str, time: Duration) -> LuaResult<()> {
+    trace!("defer {plugin_id}");
+    report::set(lua, plugin_id, time.as_secs_f64() * 1000.0)
+}
+
+/// `vlur.deferred()`: the plugins deferred at this startup,
+/// with their last sourcing time in milliseconds.
+pub fn deferred(lua: &Lua, _: ()) -> LuaResult<LuaTable<'_>> {
+    report::get(lua)
+}
+
+/// `registry[REGISTRY_KEY]: table<plugin_id, milliseconds>`
+mod report {
+    use mlua::prelude::*;
+
+    const REGISTRY_KEY: &str = concat!(env!("CARGO_PKG_NAME"), ".deferred");
+
+    fn get_reg_value(lua: &Lua) -> LuaResult<LuaTable<'_>> {
+        if let Ok(t) = lua.named_registry_value(REGISTRY_KEY) {
+            return Ok(t);
+        }
+        let t = lua.create_table()?;
+        lua.set_named_registry_value(REGISTRY_KEY, t.clone())?;
+        Ok(t)
+    }
+
+    pub fn set(lua: &Lua, plugin_id: &str, ms: f64) -> LuaResult<()> {
+        get_reg_value(lua)?.raw_set(plugin_id, ms)
+    }
+
+    /// A copy of the report, so that it cannot be modified by the caller.
+    pub fn get(lua: &Lua) -> LuaResult<LuaTable<'_>> {
+        let t = lua.create_table()?;
+        for pair in get_reg_value(lua)?.pairs::<String, f64>() {
+            let (plugin_id, ms) = pair?;
+            t.raw_set(plugin_id, ms)?;
+        }
+        Ok(t)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_format() {
+        let times = LoadTimes::parse("1500\t/foo\ninvalid\n20\t/bar baz\n");
+        assert_eq!(times.get("/foo"), Some(Duration::from_micros(1500)));
+        assert_eq!(times.get("/bar baz"), Some(Duration::from_micros(20)));
+        assert_eq!(times.get("/qux"), None);
+        assert_eq!(times.format(), "20\t/bar baz\n1500\t/foo\n");
+    }
+
+    #[test]
+    fn add() {
+        let mut times = LoadTimes::parse("1500\t/foo\n");
+        times.reset("/foo");
+        times.add("/foo", Duration::from_micros(100));
+        times.add("/foo", Duration::from_micros(200));
+        times.add("/bar", Duration::from_micros(10));
+        assert_eq!(times.get("/foo"), Some(Duration::from_micros(300)));
+        assert_eq!(times.get("/bar"), Some(Duration::from_micros(10)));
+    }
+}
@@ -1,4 +1,7 @@
-use std::{fs, path::Path};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
 
 use hashbrown::HashMap;
 use log::trace;
@@ -16,6 +19,20 @@ pub struct Cache {
 }
 
 impl Cache {
+    pub fn path(lua: &Lua) -> LuaResult<PathBuf> {
+        Ok(nvim::cache_dir(lua)?.join("cache"))
+    }
+
+    /// Remove the cache so that everything is read again at the next startup,
+    /// e.g. after the plugins have been updated.
+    pub fn invalidate(lua: &Lua) -> LuaResult<()> {
+        let path = Self::path(lua)?;
+        if path.exists() {
+            fs::remove_file(path).map_err(LuaError::external)?;
+        }
+        Ok(())
+    }
+
     pub fn read(path: &Path) -> anyhow::Result<Self> {
         trace!("restore the cache");
         let bytes = fs::read(path)?;
@@ -60,6 +77,26 @@ pub struct Inner {
 
     /// All Vim script/Lua files under the `{rtp}/plugin/` directory.
     pub plugins: HashMap<String, Vec<File>>,
+
+    /// The key is the path to the lazy plugin's directory,
+    /// and the value is the names of colorschemes in its `colors/` directory.
+    pub colors: HashMap<String, Vec<String>>,
+
+    /// The key is the path to the lazy plugin's directory,
+    /// and the value is the files in its `ftdetect/` directory.
+    pub ftdetect: HashMap<String, Vec<File>>,
+
+    /// The key is the path to the lazy plugin's directory,
+    /// and the value is the names of the top-level modules in its `lua/` directory.
+    pub modules: HashMap<String, Vec<String>>,
+
+    /// The key is the path to the lazy plugin's directory,
+    /// and the value is the patterns of the functions in its `autoload/` directory.
+    pub autoload: HashMap<String, Vec<String>>,
+
+    /// The key is the path to the plugin's directory with `lazy = 'auto'`,
+    /// and the value is the triggers found in its files.
+    pub triggers: HashMap<String, Triggers>,
 }
 
 #[derive(Archive, Deserialize, Serialize, Default)]
@@ -69,6 +106,20 @@ pub struct Package {
     pub packpath: String,
     // cache value
     pub runtimepath: RuntimePath,
+    /// The key is the name of the optional package,
+    /// and the value is the path to its directory.
+    pub opt: HashMap<String, String>,
+}
+
+#[derive(Archive, Deserialize, Serialize, Default, Clone)]
+#[archive()]
+pub struct Triggers {
+    /// User commands.
+    pub cmds: Vec<String>,
+    /// `(mode, lhs)` of `<Plug>` mappings.
+    pub keys: Vec<(String, String)>,
+    /// Patterns of autoload functions.
+    pub funcs: Vec<String>,
 }
 
 #[derive(Archive, Deserialize, Serialize, Default)]
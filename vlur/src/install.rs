@@ -4,7 +4,12 @@ use std::{path::Path, thread, time::Duration};
 
 use mlua::prelude::*;
 
-use crate::{ui::Progress, utils::expand_value};
+use crate::{
+    cache::Cache,
+    nvim,
+    ui::Progress,
+    utils::{error_message, expand_value},
+};
 
 pub fn installers(lua: &Lua) -> LuaResult<LuaTable<'_>> {
     let t = lua.create_table()?;
@@ -14,33 +19,128 @@ pub fn installers(lua: &Lua) -> LuaResult<LuaTable<'_>> {
     Ok(t)
 }
 
-pub fn install(installers: Vec<Installer>, concurrency: usize) -> LuaResult<()> {
-    if installers.is_empty() {
-        return Ok(());
+/// Number of installers running at the same time.
+pub const CONCURRENCY: usize = 5;
+
+/// Run the installers with the plugin identifiers, and report the failures.
+pub fn install(
+    lua: &Lua,
+    installers: Vec<(String, Installer)>,
+    concurrency: usize,
+) -> LuaResult<()> {
+    let failures = schedule(installers, concurrency, Installer::install)
+        .into_iter()
+        .filter_map(|(id, progress)| Some((id, progress.error?)))
+        .map(|(id, error)| format!("vlur: failed to install {id}: {error}"))
+        .collect::<Vec<_>>();
+    if !failures.is_empty() {
+        nvim::notify(lua, &failures.join("\n"), "ERROR")?;
+    }
+
+    Ok(())
+}
+
+/// `vlur.update()`: update the plugins whose installers support it,
+/// and report the revisions before and after the update.
+pub fn update(lua: &Lua, _: ()) -> LuaResult<LuaTable<'_>> {
+    let installers = registered::get(lua)?
+        .into_iter()
+        .filter(|(_, installer)| installer.0.update.is_some())
+        .collect();
+
+    let report = lua.create_table()?;
+    let mut updated = Vec::new();
+    let mut failures = Vec::new();
+    for (id, progress) in schedule(installers, CONCURRENCY, Installer::update) {
+        if let Some(error) = progress.error {
+            failures.push(format!("vlur: failed to update {id}: {error}"));
+            continue;
+        }
+        let (Some(old), Some(new)) = (progress.old_revision, progress.new_revision)
+        else {
+            continue;
+        };
+        if old != new {
+            updated.push(format!("vlur: updated {id}: {old} -> {new}"));
+        }
+        let t = lua.create_table()?;
+        t.set("old", old)?;
+        t.set("new", new)?;
+        report.set(id, t)?;
     }
 
+    if !updated.is_empty() {
+        // The files of the plugins may have been changed.
+        Cache::invalidate(lua)?;
+    }
+    if updated.is_empty() && failures.is_empty() {
+        updated.push(String::from("vlur: all plugins are up to date"));
+    }
+    if !updated.is_empty() {
+        nvim::notify(lua, &updated.join("\n"), "INFO")?;
+    }
+    if !failures.is_empty() {
+        nvim::notify(lua, &failures.join("\n"), "ERROR")?;
+    }
+
+    Ok(report)
+}
+
+/// Remember the installers for [`update()`].
+pub fn register<'lua>(
+    lua: &'lua Lua,
+    installers: Vec<(String, Installer<'lua>)>,
+) -> LuaResult<()> {
+    for (id, installer) in installers {
+        registered::set(lua, &id, installer)?;
+    }
+    Ok(())
+}
+
+/// Start the installers by `start` keeping at most `concurrency` of them
+/// running, and wait for all of them to finish.
+/// Returns the last [`Progress`] of each installer.
+fn schedule<'lua>(
+    installers: Vec<(String, Installer<'lua>)>,
+    concurrency: usize,
+    start: impl Fn(&Installer<'lua>) -> LuaResult<()>,
+) -> Vec<(String, Progress)> {
+    let failed = |e: LuaError| Progress {
+        is_finished: true,
+        error: Some(error_message(&e)),
+        ..Default::default()
+    };
+
     let mut installers = installers.into_iter();
     let mut workings = Vec::with_capacity(concurrency);
+    let mut results = Vec::new();
 
     loop {
         let workings_count = workings.len();
         if workings_count < concurrency {
-            if let Some(installer) = installers.next() {
-                installer.install()?;
-                workings.push(installer);
+            if let Some((id, installer)) = installers.next() {
+                match start(&installer) {
+                    Ok(()) => workings.push((id, installer)),
+                    Err(e) => results.push((id, failed(e))),
+                }
             } else if workings_count == 0 {
                 break;
             }
         }
-        workings.retain(|installer| match installer.progress() {
-            Ok(progress) => !progress.is_finished,
-            Err(_) => true,
+        workings.retain(|(id, installer)| {
+            let progress = match installer.progress() {
+                Ok(progress) if !progress.is_finished => return true,
+                Ok(progress) => progress,
+                Err(e) => failed(e),
+            };
+            results.push((id.clone(), progress));
+            false
         });
 
         thread::sleep(Duration::from_millis(60));
     }
 
-    Ok(())
+    results
 }
 
 #[derive(Clone)]
@@ -52,6 +152,8 @@ struct Inner<'lua> {
     setup: LuaFunction<'lua>,
     install: LuaFunction<'lua>,
     progress: LuaFunction<'lua>,
+    /// Optional.
+    update: Option<LuaFunction<'lua>>,
 }
 
 impl<'lua> Installer<'lua> {
@@ -66,6 +168,13 @@ impl<'lua> Installer<'lua> {
     pub fn progress(&self) -> LuaResult<Progress> {
         self.0.progress.call(())
     }
+
+    pub fn update(&self) -> LuaResult<()> {
+        match self.0.update {
+            Some(ref update) => update.call(()),
+            None => Ok(()),
+        }
+    }
 }
 
 impl<'lua> IntoLua<'lua> for Installer<'lua> {
@@ -88,22 +197,24 @@ impl<'lua> IntoLua<'lua> for Inner<'lua> {
 
 impl<'lua> FromLua<'lua> for Inner<'lua> {
     fn from_lua(value: LuaValue<'lua>, _lua: &'lua Lua) -> LuaResult<Self> {
-        let (setup, install, progress) = match value {
+        let (setup, install, progress, update) = match value {
             LuaValue::Table(ref t) => {
                 expand_value!(t, {
                     setup: LuaFunction,
                     install: LuaFunction,
                     progress: LuaFunction,
+                    update: Option<LuaFunction>,
                 });
-                (setup, install, progress)
+                (setup, install, progress, update)
             }
             LuaValue::UserData(ref ud) => {
                 expand_value!(ud, {
                     setup: LuaFunction,
                     install: LuaFunction,
                     progress: LuaFunction,
+                    update: Option<LuaFunction>,
                 });
-                (setup, install, progress)
+                (setup, install, progress, update)
             }
             _ => {
                 let error = LuaError::FromLuaConversionError {
@@ -118,14 +229,51 @@ impl<'lua> FromLua<'lua> for Inner<'lua> {
         let setup = setup.bind(value.clone())?;
         let install = install.bind(value.clone())?;
         let progress = progress.bind(value.clone())?;
+        let update = update.map(|f| f.bind(value.clone())).transpose()?;
 
         let handler = Self {
             value,
             setup,
             install,
             progress,
+            update,
         };
 
         Ok(handler)
     }
 }
+
+/// `registry[REGISTRY_KEY]: table<plugin_id, installer>`
+mod registered {
+    use mlua::prelude::*;
+
+    use super::Installer;
+
+    const REGISTRY_KEY: &str = concat!(env!("CARGO_PKG_NAME"), ".installers");
+
+    fn get_reg_value(lua: &Lua) -> LuaResult<LuaTable<'_>> {
+        if let Ok(t) = lua.named_registry_value(REGISTRY_KEY) {
+            return Ok(t);
+        }
+        let t = lua.create_table()?;
+        lua.set_named_registry_value(REGISTRY_KEY, t.clone())?;
+        Ok(t)
+    }
+
+    pub fn set<'lua>(
+        lua: &'lua Lua,
+        plugin_id: &str,
+        installer: Installer<'lua>,
+    ) -> LuaResult<()> {
+        get_reg_value(lua)?.raw_set(plugin_id, installer)
+    }
+
+    /// Sorted by the plugin identifiers.
+    pub fn get(lua: &Lua) -> LuaResult<Vec<(String, Installer<'_>)>> {
+        let mut installers = get_reg_value(lua)?
+            .pairs::<String, Installer>()
+            .collect::<LuaResult<Vec<_>>>()?;
+        installers.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(installers)
+    }
+}
@@ -1,44 +1,119 @@
+mod progress;
+mod repo;
+mod version;
+
 use std::{
-    io,
-    path::PathBuf,
-    process::Command,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
     thread::{self, JoinHandle},
 };
 
 use mlua::prelude::*;
+use semver::VersionReq;
 
-use crate::ui::Progress;
+use crate::{nvim, ui::Progress, utils::expand_value};
 
 pub struct Git {
     url: String,
+    rev: Revision,
     path: Option<PathBuf>,
-    thread: Option<JoinHandle<io::Result<()>>>,
+    thread: Option<JoinHandle<io::Result<Option<Revisions>>>>,
+    /// Progress reported by git in the thread.
+    state: Arc<Mutex<progress::State>>,
+    /// Result of the finished thread.
+    error: Option<String>,
+    revisions: Option<Revisions>,
+}
+
+/// Revisions before and after updating.
+type Revisions = (String, String);
+
+/// Revision to check out. The default branch is used if nothing is given.
+#[derive(Clone, Default)]
+struct Revision {
+    branch: Option<String>,
+    tag: Option<String>,
+    /// Full or abbreviated object name.
+    commit: Option<String>,
+    /// Resolved to the newest tag satisfying it when installing.
+    version: Option<VersionReq>,
 }
 
 impl LuaUserData for Git {
     fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
-        methods.add_method_mut("setup", |_lua, this, path| this.setup(path));
+        methods.add_method_mut("setup", |lua, this, path| this.setup(lua, path));
         methods.add_method_mut("install", |_lua, this, _: ()| this.install());
         methods.add_method_mut("progress", |_lua, this, _: ()| this.progress());
+        methods.add_method_mut("update", |_lua, this, _: ()| this.update());
     }
 }
 
 impl<'lua> Git {
-    pub fn new(_lua: &'lua Lua, url: LuaString<'lua>) -> LuaResult<Self> {
+    pub fn new(
+        _lua: &'lua Lua,
+        (url, opts): (LuaString<'lua>, Option<LuaTable<'lua>>),
+    ) -> LuaResult<Self> {
         let url = url.to_str()?.to_string();
+        let rev = match opts {
+            Some(opts) => {
+                expand_value!(opts, {
+                    branch: Option<String>,
+                    tag: Option<String>,
+                    commit: Option<String>,
+                    version: Option<String>,
+                });
+                if branch.is_some() && tag.is_some() {
+                    return Err(LuaError::runtime(
+                        "install.git: `branch` and `tag` cannot be used together",
+                    ));
+                }
+                if version.is_some()
+                    && (branch.is_some() || tag.is_some() || commit.is_some())
+                {
+                    return Err(LuaError::runtime(
+                        "install.git: `version` cannot be used with `branch`, `tag` or `commit`",
+                    ));
+                }
+                let version = version
+                    .map(|v| VersionReq::parse(&v))
+                    .transpose()
+                    .map_err(|e| LuaError::runtime(format!("install.git: {e}")))?;
+                Revision {
+                    branch,
+                    tag,
+                    commit,
+                    version,
+                }
+            }
+            None => Revision::default(),
+        };
         Ok(Self {
             url,
+            rev,
             path: None,
             thread: None,
+            state: Arc::default(),
+            error: None,
+            revisions: None,
         })
     }
 
-    fn setup(&mut self, path: LuaString<'lua>) -> LuaResult<bool> {
+    fn setup(&mut self, lua: &'lua Lua, path: LuaString<'lua>) -> LuaResult<bool> {
         debug_assert!(self.path.is_none());
         debug_assert!(self.thread.is_none());
 
         let path = PathBuf::from(path.to_str()?.to_string());
         let result = path.exists();
+        if result && !self.rev.is_checked_out(&path) {
+            let msg = format!(
+                "vlur: {} is not checked out at the {}",
+                path.display(),
+                self.rev
+            );
+            nvim::notify(lua, &msg, "WARN")?;
+        }
         self.path = Some(path);
 
         Ok(result)
@@ -49,28 +124,306 @@ impl<'lua> Git {
             return Ok(());
         }
 
-        let url = self.url.as_str();
-        let path = self.path.as_ref().unwrap();
-        let mut cmd = Command::new("git");
-        cmd.args(["clone", url]).arg(path.as_os_str());
+        let url = self.url.clone();
+        let path = self.path.clone().unwrap();
+        let rev = self.rev.clone();
+        let state = self.reset();
 
-        let thread = thread::spawn(move || {
-            cmd.status()?;
-            Ok(())
-        });
+        let thread =
+            thread::spawn(move || clone(&url, &path, &rev, &state).map(|()| None));
         self.thread = Some(thread);
 
         Ok(())
     }
 
+    fn update(&mut self) -> LuaResult<()> {
+        if self.thread.is_some() {
+            return Ok(());
+        }
+        let Some(path) = self.path.clone().filter(|path| path.exists()) else {
+            // Nothing to update, e.g. failed to install. `progress()` reports
+            // neither revisions nor an error, so it is skipped.
+            return Ok(());
+        };
+
+        let url = self.url.clone();
+        let rev = self.rev.clone();
+        let state = self.reset();
+
+        let thread = thread::spawn(move || update(&url, &path, &rev, &state).map(Some));
+        self.thread = Some(thread);
+
+        Ok(())
+    }
+
+    /// Clear the result of the previous thread, and return the new state
+    /// for the next one.
+    fn reset(&mut self) -> Arc<Mutex<progress::State>> {
+        self.error = None;
+        self.revisions = None;
+        self.state = Arc::default();
+        self.state.clone()
+    }
+
     fn progress(&mut self) -> LuaResult<Progress> {
-        let is_finished = match self.thread {
-            Some(ref thread) => thread.is_finished(),
-            None => true,
+        if let Some(thread) = self.thread.take_if(|thread| thread.is_finished()) {
+            match thread.join() {
+                Ok(Ok(revisions)) => self.revisions = revisions,
+                Ok(Err(e)) => self.error = Some(e.to_string()),
+                Err(_) => {
+                    self.error = Some(String::from("the thread running git panicked"))
+                }
+            }
+        }
+        let state = self.state.lock().unwrap();
+        Ok(Progress {
+            is_finished: self.thread.is_none(),
+            ratio: state.ratio,
+            message: state.message.clone(),
+            error: self.error.clone(),
+            old_revision: self.revisions.as_ref().map(|(old, _)| old.clone()),
+            new_revision: self.revisions.as_ref().map(|(_, new)| new.clone()),
+        })
+    }
+}
+
+impl Revision {
+    /// Whether the checkout in `path` is at this revision.
+    fn is_checked_out(&self, path: &Path) -> bool {
+        if self.branch.is_none()
+            && self.tag.is_none()
+            && self.commit.is_none()
+            && self.version.is_none()
+        {
+            return true;
+        }
+        let Some(git_dir) = repo::git_dir(path) else {
+            // Not a git repository, e.g. a local copy.
+            return true;
         };
-        if is_finished {
-            self.thread = None;
+
+        if let Some(ref req) = self.version {
+            // The constraint may have been changed since it was resolved.
+            if repo::read_config(&git_dir, "vlur", "version") != Some(req.to_string()) {
+                return false;
+            }
+            let commit = repo::read_config(&git_dir, "vlur", "commit");
+            return commit.is_some() && repo::head_commit(&git_dir) == commit;
+        }
+        if let Some(ref commit) = self.commit {
+            return repo::head_commit(&git_dir)
+                .is_some_and(|head| head.starts_with(commit));
+        }
+        if let Some(ref tag) = self.tag {
+            let Some(head) = repo::head_commit(&git_dir) else {
+                return false;
+            };
+            if repo::resolve_ref(&git_dir, &format!("refs/tags/{tag}")).contains(&head) {
+                return true;
+            }
+            // Annotated tags stored as loose objects cannot be peeled without git.
+            return rev_parse(path, &[&format!("{tag}^{{commit}}")])
+                .is_some_and(|c| c == head);
         }
-        Ok(Progress { is_finished })
+        if let Some(ref branch) = self.branch {
+            return matches!(
+                repo::read_head(&git_dir),
+                Some(repo::Head::Branch(ref b)) if b == branch
+            );
+        }
+
+        true
+    }
+}
+
+impl fmt::Display for Revision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref req) = self.version {
+            write!(f, "version {req}")
+        } else if let Some(ref commit) = self.commit {
+            write!(f, "commit {commit}")
+        } else if let Some(ref tag) = self.tag {
+            write!(f, "tag {tag}")
+        } else if let Some(ref branch) = self.branch {
+            write!(f, "branch {branch}")
+        } else {
+            write!(f, "default branch")
+        }
+    }
+}
+
+fn clone(
+    url: &str,
+    path: &Path,
+    rev: &Revision,
+    state: &Mutex<progress::State>,
+) -> io::Result<()> {
+    let resolved = match rev.version {
+        Some(ref req) => Some(version::resolve(url, req)?),
+        None => None,
+    };
+
+    let mut cmd = Command::new("git");
+    cmd.args(["clone", "--progress"]);
+    let name = resolved.as_ref().map(|(tag, _)| tag);
+    if let Some(name) = name.or(rev.tag.as_ref()).or(rev.branch.as_ref()) {
+        cmd.args(["--branch", name]);
+    }
+    cmd.arg(url).arg(path);
+    run_with_progress(&mut cmd, state)?;
+
+    let result = (|| {
+        if let Some(ref commit) = rev.commit {
+            git(path, &["checkout", "--quiet", commit])?;
+        }
+        if let (Some(req), Some((tag, commit))) = (&rev.version, &resolved) {
+            record_version(path, req, tag, commit)?;
+        }
+        Ok(())
+    })();
+    // Otherwise the plugin would be treated as installed from the next time.
+    if result.is_err() {
+        fs::remove_dir_all(path).ok();
+    }
+
+    result
+}
+
+/// Fetch the remote, and move to the pinned revision or fast-forward
+/// the tracked branch. Returns the revisions before and after that.
+fn update(
+    url: &str,
+    path: &Path,
+    rev: &Revision,
+    state: &Mutex<progress::State>,
+) -> io::Result<Revisions> {
+    let old = short_head(path)?;
+
+    // The constraint may be satisfied by a newer tag now.
+    let resolved = match rev.version {
+        Some(ref req) => Some(version::resolve(url, req)?),
+        None => None,
+    };
+
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(path)
+        .args(["fetch", "--progress", "--tags", "origin"]);
+    run_with_progress(&mut cmd, state)?;
+
+    if let (Some(req), Some((tag, commit))) = (&rev.version, &resolved) {
+        git(path, &["checkout", "--quiet", tag])?;
+        record_version(path, req, tag, commit)?;
+    } else if let Some(ref commit) = rev.commit {
+        git(path, &["checkout", "--quiet", commit])?;
+    } else if let Some(ref tag) = rev.tag {
+        git(path, &["checkout", "--quiet", tag])?;
+    } else {
+        let branch = match rev.branch {
+            Some(ref branch) => Some(branch.clone()),
+            // Left by a pin which has been removed, which has no upstream.
+            None if is_detached(path) => Some(default_branch(path)?),
+            None => None,
+        };
+        if let Some(ref branch) = branch {
+            git(path, &["checkout", "--quiet", branch])?;
+        }
+        git(path, &["merge", "--ff-only", "--quiet", "@{upstream}"])?;
+    }
+
+    let new = short_head(path)?;
+    Ok((old, new))
+}
+
+/// Record the resolved version so that the checkout can be checked by
+/// `setup`, e.g. `git config vlur.tag v2.3.1`.
+fn record_version(
+    path: &Path,
+    req: &VersionReq,
+    tag: &str,
+    commit: &str,
+) -> io::Result<()> {
+    git(path, &["config", "vlur.version", &req.to_string()])?;
+    git(path, &["config", "vlur.tag", tag])?;
+    git(path, &["config", "vlur.commit", commit])
+}
+
+/// `git rev-parse --short HEAD`
+fn short_head(path: &Path) -> io::Result<String> {
+    rev_parse(path, &["--short", "HEAD"]).ok_or_else(|| {
+        io::Error::other(format!("failed to read HEAD of {}", path.display()))
+    })
+}
+
+fn is_detached(path: &Path) -> bool {
+    let head = repo::git_dir(path).and_then(|git_dir| repo::read_head(&git_dir));
+    matches!(head, Some(repo::Head::Detached(_)))
+}
+
+/// The default branch of the remote, e.g. `main` for `origin/HEAD`.
+fn default_branch(path: &Path) -> io::Result<String> {
+    rev_parse(path, &["--abbrev-ref", "origin/HEAD"])
+        .and_then(|name| Some(name.strip_prefix("origin/")?.to_string()))
+        .ok_or_else(|| {
+            io::Error::other(format!(
+                "failed to read the default branch of {}",
+                path.display()
+            ))
+        })
+}
+
+/// `git -C {path} {args}`
+fn git(path: &Path, args: &[&str]) -> io::Result<()> {
+    run(Command::new("git").arg("-C").arg(path).args(args))
+}
+
+/// Run the command with its output captured, which is used as the error
+/// message if it fails.
+fn run(cmd: &mut Command) -> io::Result<()> {
+    let output = cmd.output()?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let msg = match stderr.trim() {
+        "" => stdout.trim(),
+        stderr => stderr,
+    };
+    Err(io::Error::other(format!("{msg} ({})", output.status)))
+}
+
+/// Like [`run()`], but parse the progress in stderr while running.
+fn run_with_progress(
+    cmd: &mut Command,
+    state: &Mutex<progress::State>,
+) -> io::Result<()> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    progress::read(child.stderr.take().unwrap(), state)?;
+
+    let status = child.wait()?;
+    if status.success() {
+        return Ok(());
+    }
+    let output = state.lock().unwrap().output();
+    Err(io::Error::other(format!("{output} ({status})")))
+}
+
+/// `git rev-parse --verify --quiet {args}`
+fn rev_parse(path: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(path)
+        .args(["rev-parse", "--verify", "--quiet"])
+        .args(args)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
     }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
 }
@@ -0,0 +1,137 @@
+//! Parse the progress which `git clone --progress` writes to stderr.
+
+use std::{
+    io::{self, Read},
+    sync::Mutex,
+};
+
+#[derive(Default)]
+pub struct State {
+    /// Ratio of the received objects, from `0.0` to `1.0`.
+    pub ratio: Option<f64>,
+    /// The last progress line, e.g. `Receiving objects:  45% (450/1000)`.
+    pub message: Option<String>,
+    /// Lines other than progress, used as the error message.
+    pub output: Vec<String>,
+}
+
+impl State {
+    /// What git printed, used as the error message. The last progress line
+    /// is used if git printed nothing else.
+    pub fn output(&self) -> String {
+        match self.message {
+            Some(ref message) if self.output.is_empty() => message.clone(),
+            _ => self.output.join("\n"),
+        }
+    }
+
+    fn update(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        match parse_line(line) {
+            Some((phase, percent)) => {
+                if phase == "Receiving objects" {
+                    self.ratio = Some(percent / 100.0);
+                }
+                self.message = Some(line.to_string());
+            }
+            // Printed at the beginning of every clone, which is just noise in errors.
+            None if line.starts_with("Cloning into ") => (),
+            None => self.output.push(line.to_string()),
+        }
+    }
+}
+
+/// Feed `stderr` into `state` until EOF. Progress lines are terminated by
+/// `\r` so that they overwrite each other in a terminal.
+pub fn read(mut stderr: impl Read, state: &Mutex<State>) -> io::Result<()> {
+    let mut buf = [0; 1024];
+    let mut line = Vec::new();
+    loop {
+        let n = stderr.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        for &byte in &buf[..n] {
+            if byte == b'\r' || byte == b'\n' {
+                state
+                    .lock()
+                    .unwrap()
+                    .update(&String::from_utf8_lossy(&line));
+                line.clear();
+            } else {
+                line.push(byte);
+            }
+        }
+    }
+    state
+        .lock()
+        .unwrap()
+        .update(&String::from_utf8_lossy(&line));
+    Ok(())
+}
+
+/// `[remote: ]{phase}: {percent}% ...`
+fn parse_line(line: &str) -> Option<(&str, f64)> {
+    let line = line.strip_prefix("remote:").unwrap_or(line).trim_start();
+    let (phase, rest) = line.split_once(':')?;
+    let (percent, _) = rest.trim_start().split_once('%')?;
+    Some((phase, percent.parse().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line() {
+        assert_eq!(
+            parse_line("Receiving objects:  45% (450/1000), 1.20 MiB | 2.00 MiB/s"),
+            Some(("Receiving objects", 45.0))
+        );
+        assert_eq!(
+            parse_line("remote: Counting objects: 100% (5/5), done."),
+            Some(("Counting objects", 100.0))
+        );
+        assert_eq!(parse_line("Cloning into 'foo'..."), None);
+        assert_eq!(parse_line("fatal: repository 'foo' does not exist"), None);
+    }
+
+    #[test]
+    fn read_stderr() {
+        let stderr = "Cloning into 'foo'...\n\
+            remote: Enumerating objects: 10, done.\n\
+            Receiving objects:   0% (0/10)\r\
+            Receiving objects:  50% (5/10)\r\
+            Receiving objects: 100% (10/10), done.\n\
+            Resolving deltas:  50% (1/2)\r\
+            warning: remote HEAD refers to nonexistent ref\n";
+        let state = Mutex::new(State::default());
+        read(stderr.as_bytes(), &state).unwrap();
+
+        let state = state.into_inner().unwrap();
+        assert_eq!(state.ratio, Some(1.0));
+        assert_eq!(
+            state.message.as_deref(),
+            Some("Resolving deltas:  50% (1/2)")
+        );
+        assert_eq!(
+            state.output,
+            vec![
+                "remote: Enumerating objects: 10, done.",
+                "warning: remote HEAD refers to nonexistent ref",
+            ]
+        );
+    }
+
+    #[test]
+    fn output_without_errors() {
+        let state = Mutex::new(State::default());
+        read("Receiving objects:  50% (5/10)\r".as_bytes(), &state).unwrap();
+
+        let state = state.into_inner().unwrap();
+        assert_eq!(state.output(), "Receiving objects:  50% (5/10)");
+    }
+}
@@ -0,0 +1,172 @@
+//! Read the state of a checkout from the files in `.git/`,
+//! which is much faster than spawning git at every startup.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub enum Head {
+    /// `ref: refs/heads/{name}`
+    Branch(String),
+    /// Object name of the checked out commit.
+    Detached(String),
+}
+
+/// `{path}/.git`, or the directory referred to by it if it is a file,
+/// e.g. in submodules.
+pub fn git_dir(path: &Path) -> Option<PathBuf> {
+    let dot_git = path.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let content = fs::read_to_string(&dot_git).ok()?;
+    let dir = content.strip_prefix("gitdir:")?.trim();
+    Some(path.join(dir))
+}
+
+pub fn read_head(git_dir: &Path) -> Option<Head> {
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    let r = match head.strip_prefix("ref: refs/heads/") {
+        Some(branch) => Head::Branch(branch.to_string()),
+        None => Head::Detached(head.to_string()),
+    };
+    Some(r)
+}
+
+/// Object name of the checked out commit.
+pub fn head_commit(git_dir: &Path) -> Option<String> {
+    match read_head(git_dir)? {
+        Head::Branch(branch) => {
+            resolve_ref(git_dir, &format!("refs/heads/{branch}")).pop()
+        }
+        Head::Detached(commit) => Some(commit),
+    }
+}
+
+/// Object names which the ref, e.g. `refs/tags/v1.0`, points to.
+///
+/// For an annotated tag, this also contains the tagged commit
+/// if it is found in `packed-refs`.
+pub fn resolve_ref(git_dir: &Path, name: &str) -> Vec<String> {
+    if let Ok(content) = fs::read_to_string(git_dir.join(name)) {
+        return vec![content.trim().to_string()];
+    }
+    let Ok(content) = fs::read_to_string(git_dir.join("packed-refs")) else {
+        return Vec::new();
+    };
+    parse_packed_refs(&content, name)
+}
+
+fn parse_packed_refs(content: &str, name: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut matched = false;
+    for line in content.lines() {
+        if line.starts_with('#') {
+            continue;
+        }
+        // The peeled object of the previous line.
+        if let Some(peeled) = line.strip_prefix('^') {
+            if matched {
+                names.push(peeled.to_string());
+            }
+            continue;
+        }
+        matched = match line.split_once(' ') {
+            Some((object, r)) if r == name => {
+                names.push(object.to_string());
+                true
+            }
+            _ => false,
+        };
+    }
+    names
+}
+
+/// Value of `{section}.{key}` in `{git_dir}/config`,
+/// e.g. the ones recorded by `git config vlur.tag v1.0`.
+pub fn read_config(git_dir: &Path, section: &str, key: &str) -> Option<String> {
+    let content = fs::read_to_string(git_dir.join("config")).ok()?;
+    parse_config(&content, section, key)
+}
+
+fn parse_config(content: &str, section: &str, key: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            in_section = header
+                .strip_suffix(']')
+                .is_some_and(|name| name.trim().eq_ignore_ascii_case(section));
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        let Some((k, value)) = line.split_once('=') else {
+            continue;
+        };
+        if k.trim().eq_ignore_ascii_case(key) {
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packed_refs() {
+        let content = "\
+# pack-refs with: peeled fully-peeled sorted
+1111111111111111111111111111111111111111 refs/heads/main
+2222222222222222222222222222222222222222 refs/tags/v1.0
+^3333333333333333333333333333333333333333
+4444444444444444444444444444444444444444 refs/tags/v1.1
+";
+        assert_eq!(
+            parse_packed_refs(content, "refs/heads/main"),
+            vec!["1111111111111111111111111111111111111111"]
+        );
+        assert_eq!(
+            parse_packed_refs(content, "refs/tags/v1.0"),
+            vec![
+                "2222222222222222222222222222222222222222",
+                "3333333333333333333333333333333333333333",
+            ]
+        );
+        assert_eq!(
+            parse_packed_refs(content, "refs/tags/v1.1"),
+            vec!["4444444444444444444444444444444444444444"]
+        );
+        assert!(parse_packed_refs(content, "refs/tags/v2.0").is_empty());
+    }
+
+    #[test]
+    fn config() {
+        let content = "\
+[core]
+\tbare = false
+[remote \"origin\"]
+\turl = https://example.com/foo.git
+[vlur]
+\tversion = \">=1.0, <2.0\"
+\ttag = v1.2.0
+";
+        assert_eq!(parse_config(content, "vlur", "tag"), Some("v1.2.0".into()));
+        assert_eq!(
+            parse_config(content, "vlur", "version"),
+            Some(">=1.0, <2.0".into())
+        );
+        assert_eq!(parse_config(content, "vlur", "commit"), None);
+        assert_eq!(parse_config(content, "core", "url"), None);
+    }
+}
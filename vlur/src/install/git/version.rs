@@ -0,0 +1,117 @@
+//! Resolve a semver constraint to one of the tags of the remote repository.
+
+use std::{io, process::Command};
+
+use semver::{Version, VersionReq};
+
+/// Find the newest tag satisfying `req` in the remote repository,
+/// and return it with the tagged commit.
+pub fn resolve(url: &str, req: &VersionReq) -> io::Result<(String, String)> {
+    let output = Command::new("git")
+        .args(["ls-remote", "--tags", url])
+        .output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(format!(
+            "failed to list the tags of {url}: {} ({})",
+            stderr.trim(),
+            output.status
+        )));
+    }
+
+    let tags = parse_ls_remote(&String::from_utf8_lossy(&output.stdout));
+    select(tags, req)
+        .ok_or_else(|| io::Error::other(format!("no tags of {url} satisfy {req}")))
+}
+
+/// Parse the output of `git ls-remote --tags` into `(tag, commit)`.
+fn parse_ls_remote(output: &str) -> Vec<(String, String)> {
+    let mut tags: Vec<(String, String)> = Vec::new();
+    for line in output.lines() {
+        let Some((object, name)) = line.split_once('\t') else {
+            continue;
+        };
+        let Some(name) = name.strip_prefix("refs/tags/") else {
+            continue;
+        };
+        // Annotated tags are followed by the peeled commit.
+        if let Some(name) = name.strip_suffix("^{}") {
+            if let Some(tag) = tags.iter_mut().find(|(tag, _)| tag == name) {
+                tag.1 = object.to_string();
+            }
+            continue;
+        }
+        tags.push((name.to_string(), object.to_string()));
+    }
+    tags
+}
+
+/// The newest tag satisfying `req`, e.g. `v2.3.1` for `^2.1`.
+fn select(tags: Vec<(String, String)>, req: &VersionReq) -> Option<(String, String)> {
+    tags.into_iter()
+        .filter_map(|(tag, commit)| {
+            let version = tag.strip_prefix(['v', 'V']).unwrap_or(&tag);
+            let version = Version::parse(version).ok()?;
+            req.matches(&version).then_some((version, tag, commit))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, tag, commit)| (tag, commit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(names: &[&str]) -> Vec<(String, String)> {
+        names
+            .iter()
+            .map(|name| (name.to_string(), format!("{name}-commit")))
+            .collect()
+    }
+
+    #[test]
+    fn ls_remote() {
+        let output = "\
+1111111111111111111111111111111111111111\trefs/tags/v1.0.0
+2222222222222222222222222222222222222222\trefs/tags/v2.0.0
+3333333333333333333333333333333333333333\trefs/tags/v2.0.0^{}
+";
+        assert_eq!(
+            parse_ls_remote(output),
+            vec![
+                (
+                    String::from("v1.0.0"),
+                    String::from("1111111111111111111111111111111111111111")
+                ),
+                (
+                    String::from("v2.0.0"),
+                    String::from("3333333333333333333333333333333333333333")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn select_newest() {
+        let tags = tags(&[
+            "v1.9.0",
+            "v2.0.5",
+            "v2.1.0",
+            "2.3.1",
+            "v2.4.0-rc.1",
+            "v3.0.0",
+            "nightly",
+        ]);
+        let select = |req: &str| {
+            select(tags.clone(), &VersionReq::parse(req).unwrap()).map(|(tag, _)| tag)
+        };
+        assert_eq!(select("^2.1"), Some(String::from("2.3.1")));
+        assert_eq!(select("~2.0"), Some(String::from("v2.0.5")));
+        assert_eq!(select("*"), Some(String::from("v3.0.0")));
+        assert_eq!(
+            select(">=2.4.0-rc.1, <3"),
+            Some(String::from("v2.4.0-rc.1"))
+        );
+        assert_eq!(select("^4"), None);
+    }
+}
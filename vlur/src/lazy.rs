@@ -1,17 +1,105 @@
+mod after;
+mod cmd;
+mod colorscheme;
+mod combinator;
 mod event;
+mod ft;
+mod func;
+mod keys;
+mod lsp;
+mod module;
+mod root;
+mod very_lazy;
+
+use std::{env, path::Path};
 
 use mlua::prelude::*;
 
-use crate::utils::expand_value;
+use crate::{cache::Triggers, nvim, runtimepath::RuntimePath, utils::expand_value};
+
+pub use very_lazy::schedule as schedule_very_lazy;
 
 pub fn handlers(lua: &Lua) -> LuaResult<LuaTable<'_>> {
     let t = lua.create_table()?;
 
     t.set("event", lua.create_function(event::Event::new)?)?;
+    t.set("cmd", lua.create_function(cmd::Cmd::new)?)?;
+    t.set("keys", lua.create_function(keys::Keys::new)?)?;
+    t.set("ft", lua.create_function(ft::Ft::new)?)?;
+    t.set("module", lua.create_function(module::Module::new)?)?;
+    t.set(
+        "colorscheme",
+        lua.create_function(colorscheme::Colorscheme::new)?,
+    )?;
+    t.set("func", lua.create_function(func::Func::new)?)?;
+    t.set("after", lua.create_function(after::After::new)?)?;
+    t.set("root", lua.create_function(root::Root::new)?)?;
+    t.set("lsp", lua.create_function(lsp::Lsp::new)?)?;
+    t.set("any", lua.create_function(combinator::Any::new)?)?;
+    t.set("all", lua.create_function(combinator::All::new)?)?;
+    t.set("when", lua.create_function(combinator::When::new)?)?;
+
+    Ok(t)
+}
+
+/// Handlers for `lazy = 'auto'`, created from the triggers found in the plugin.
+pub fn auto_handlers<'lua>(
+    lua: &'lua Lua,
+    triggers: &Triggers,
+) -> LuaResult<LuaTable<'lua>> {
+    let t = lua.create_table()?;
+
+    if !triggers.cmds.is_empty() {
+        let cmd = triggers.cmds.clone().into_lua(lua)?;
+        t.raw_push(cmd::Cmd::new(lua, cmd)?)?;
+    }
+    if !triggers.keys.is_empty() {
+        let keys = lua.create_table()?;
+        for (mode, lhs) in &triggers.keys {
+            let key = lua.create_table()?;
+            key.raw_push(lhs.as_str())?;
+            key.raw_set("mode", mode.as_str())?;
+            keys.raw_push(key)?;
+        }
+        t.raw_push(keys::Keys::new(lua, LuaValue::Table(keys))?)?;
+    }
+    if !triggers.funcs.is_empty() {
+        let funcs = triggers.funcs.clone().into_lua(lua)?;
+        t.raw_push(func::Func::new(lua, Some(funcs))?)?;
+    }
 
     Ok(t)
 }
 
+/// Handlers for the plugins deferred by `lazy = 'adaptive'`.
+pub fn adaptive_handlers(lua: &Lua) -> LuaResult<LuaTable<'_>> {
+    let t = lua.create_table()?;
+    let event = vec![String::from("User")];
+    let pattern = vec![String::from(very_lazy::PATTERN)];
+    t.raw_push(event::Event::with_pattern(event, pattern))?;
+
+    Ok(t)
+}
+
+/// Convert `string | string[]` into [`Vec<String>`].
+fn value_to_vec(value: LuaValue) -> LuaResult<Vec<String>> {
+    match value {
+        LuaValue::String(s) => Ok(vec![s.to_str()?.to_string()]),
+        LuaValue::Table(t) => Ok(t
+            .sequence_values::<String>()
+            .filter_map(|v| v.ok())
+            .collect()),
+        _ => {
+            let err = LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "string or table",
+                message: None,
+            };
+            Err(err)
+        }
+    }
+}
+
 pub struct Handler<'lua>(Inner<'lua>);
 
 struct Inner<'lua> {
@@ -21,29 +109,42 @@ struct Inner<'lua> {
 }
 
 impl<'lua> Handler<'lua> {
+    /// Start the handler for the plugin registered by [`register()`].
     pub fn start(
         &mut self,
         lua: &'lua Lua,
         plugin_id: LuaString<'lua>,
+        plugin: LuaTable<'lua>,
+    ) -> LuaResult<()> {
+        self.0.bind(lua, plugin_id)?;
+        self.0.start.call(plugin)
+    }
+
+    /// Start the handler with `plugin_loader` as is.
+    ///
+    /// Unlike [`Handler::start()`], this neither registers the handler to be
+    /// stopped nor stops other handlers after loading, so that it can be
+    /// composed by other handlers.
+    fn start_with(
+        &self,
         plugin_loader: LuaFunction<'lua>,
+        plugin: LuaTable<'lua>,
     ) -> LuaResult<()> {
-        self.0.bind(lua, plugin_id, plugin_loader)?;
-        self.0.start.call(())
+        self.0.start.call((plugin_loader, plugin))
+    }
+
+    fn stop(&self) -> LuaResult<()> {
+        self.0.stop.call(())
     }
 }
 
 impl<'lua> Inner<'lua> {
-    fn bind(
-        &mut self,
-        lua: &'lua Lua,
-        plugin_id: LuaString<'lua>,
-        plugin_loader: LuaFunction<'lua>,
-    ) -> LuaResult<()> {
+    fn bind(&mut self, lua: &'lua Lua, plugin_id: LuaString<'lua>) -> LuaResult<()> {
         stop_funcs::set(lua, plugin_id.clone(), self.stop.clone())?;
 
         let plugin_loader = lua
             .create_function(load_plugin_and_stop_handlers)?
-            .bind((plugin_id.clone(), plugin_loader.clone()))?;
+            .bind(plugin_id)?;
 
         self.start = self.start.bind(plugin_loader)?;
 
@@ -51,21 +152,179 @@ impl<'lua> Inner<'lua> {
     }
 }
 
-fn load_plugin_and_stop_handlers(
-    lua: &Lua,
-    (plugin_id, plugin_loader): (LuaString, LuaFunction),
+/// Register the loader of a lazy plugin, which is called at most once
+/// by [`load()`] or the plugin's handlers.
+pub fn register<'lua>(
+    lua: &'lua Lua,
+    plugin_id: LuaString<'lua>,
+    plugin_loader: LuaFunction<'lua>,
 ) -> LuaResult<()> {
-    plugin_loader.call(())?;
+    loaders::set(lua, plugin_id, plugin_loader)
+}
+
+/// Mark the plugin as loaded, e.g. a plugin which is not lazy.
+pub fn set_loaded(lua: &Lua, plugin_id: LuaString) -> LuaResult<()> {
+    loaded::set(lua, plugin_id)
+}
+
+fn is_loaded(lua: &Lua, plugin_id: &str) -> LuaResult<bool> {
+    loaded::contains(lua, plugin_id)
+}
+
+/// Whether the plugin is registered by [`register()`] and not loaded yet.
+fn is_registered(lua: &Lua, plugin_id: &str) -> LuaResult<bool> {
+    loaders::contains(lua, plugin_id)
+}
+
+/// Load the plugin if it is not loaded yet.
+pub fn load<'lua>(lua: &'lua Lua, plugin_id: LuaString<'lua>) -> LuaResult<()> {
+    let id = plugin_id.to_str()?;
+    if !is_registered(lua, id)? && !is_loaded(lua, id)? {
+        return Err(LuaError::runtime(format!("plugin `{id}` does not exist")));
+    }
+    load_plugin_and_stop_handlers(lua, plugin_id)?;
+    Ok(())
+}
+
+/// Hold the plugins loaded by the handlers from now on until
+/// [`release_loads()`], e.g. the ones loaded as soon as their handlers start.
+pub fn hold_loads(lua: &Lua) -> LuaResult<()> {
+    held::start(lua)
+}
+
+/// Load the plugins held since [`hold_loads()`] in that order.
+pub fn release_loads(lua: &Lua) -> LuaResult<()> {
+    for plugin_id in held::take(lua)? {
+        load_plugin_and_stop_handlers(lua, plugin_id)?;
+    }
 
+    Ok(())
+}
+
+/// Load all plugins which are not loaded yet.
+pub fn load_all(lua: &Lua, _: ()) -> LuaResult<()> {
+    for plugin_id in loaders::ids(lua)? {
+        load(lua, plugin_id)?;
+    }
+
+    Ok(())
+}
+
+/// Return whether the plugin is loaded, which tells the handlers that
+/// they can replay their triggers. It is not if held by [`hold_loads()`].
+fn load_plugin_and_stop_handlers<'lua>(
+    lua: &'lua Lua,
+    plugin_id: LuaString<'lua>,
+) -> LuaResult<bool> {
+    if held::push(lua, plugin_id.clone())? {
+        return Ok(false);
+    }
+    let Some(plugin_loader) = loaders::take(lua, plugin_id.clone())? else {
+        // already loaded
+        return Ok(true);
+    };
+
+    // Stop the handlers first, so that stubs created by them (e.g. user commands)
+    // don't overwrite the ones defined by the plugin.
+    stop_handlers(lua, plugin_id.clone())?;
+
+    if let Err(err) = plugin_loader.call::<_, ()>(()) {
+        // The handlers are already stopped, so keep the loader for e.g.
+        // `vlur.load()` to retry.
+        loaders::set(lua, plugin_id, plugin_loader)?;
+        return Err(err);
+    }
+    loaded::set(lua, plugin_id.clone())?;
+
+    after::fire(lua, plugin_id.to_str()?)?;
+
+    Ok(true)
+}
+
+/// Call the `plugin_loader` given to a handler, and return whether the
+/// plugin has been loaded. The loaders created by the combinators return
+/// `false` when they are waiting for other triggers, and the ones written
+/// in Lua may return nothing, which is treated as loaded.
+fn call_loader(plugin_loader: &LuaFunction) -> LuaResult<bool> {
+    Ok(plugin_loader.call::<_, Option<bool>>(())?.unwrap_or(true))
+}
+
+/// Stop the handlers started for the plugin, e.g. when one of them fails
+/// to start.
+pub fn stop_handlers<'lua>(lua: &'lua Lua, plugin_id: LuaString<'lua>) -> LuaResult<()> {
     for f in stop_funcs::get(lua, plugin_id.clone())?.sequence_values() {
         let f: LuaFunction = f?;
-        f.call(())?;
+        f.call::<_, ()>(())?;
     }
-    stop_funcs::clear(lua, plugin_id)?;
+    stop_funcs::clear(lua, plugin_id)
+}
+
+/// Treat the plugin as loaded when `:packadd` adds it to `&runtimepath`,
+/// instead of loading it again by the handlers.
+pub fn watch_packadd<'lua>(
+    lua: &'lua Lua,
+    plugin_id: LuaString<'lua>,
+    path: &Path,
+) -> LuaResult<()> {
+    // `OptionSet` is not triggered during startup, where `:packadd` is
+    // found by the files sourced by it instead.
+    let pattern = format!("{}/*", path.display());
+    let callback = lua.create_function(on_packadd)?.bind(plugin_id.clone())?;
+    nvim::create_autocmd(lua, "SourcePre", pattern, callback, true)?;
+
+    // The autocommand deletes itself once all the packages are loaded.
+    if packages::is_empty(lua)? {
+        let callback = lua.create_function(|lua, _: LuaValue| on_rtp_changed(lua))?;
+        nvim::create_autocmd(lua, "OptionSet", "runtimepath", callback, false)?;
+    }
+    packages::set(lua, path.to_str().unwrap(), plugin_id)?;
 
     Ok(())
 }
 
+/// Find the packages added by `:packadd`, which may be relative to the
+/// working directory like `&packpath`.
+///
+/// Return `true` to delete the autocommand when no packages are left to
+/// watch.
+fn on_rtp_changed(lua: &Lua) -> LuaResult<bool> {
+    let cwd = env::current_dir().unwrap_or_default();
+    let rtp: RuntimePath = nvim::get_opt(lua, "runtimepath")?;
+    for dir in &rtp {
+        let path = cwd.join(dir);
+        if let Some(plugin_id) = packages::get(lua, path.to_str().unwrap())? {
+            on_packadd(lua, plugin_id)?;
+        }
+    }
+
+    // The packages may also have been loaded by the handlers.
+    for (path, plugin_id) in packages::entries(lua)? {
+        if !loaders::contains(lua, plugin_id.to_str()?)? {
+            packages::remove(lua, path.to_str()?)?;
+        }
+    }
+
+    packages::is_empty(lua)
+}
+
+fn on_packadd<'lua>(lua: &'lua Lua, plugin_id: LuaString<'lua>) -> LuaResult<()> {
+    if loaders::take(lua, plugin_id.clone())?.is_none() {
+        // sourced by the loader
+        return Ok(());
+    }
+
+    stop_handlers(lua, plugin_id.clone())?;
+    loaded::set(lua, plugin_id.clone())?;
+
+    // `:packadd` is still adding the plugin.
+    let fire = lua
+        .create_function(|lua, plugin_id: LuaString| {
+            after::fire(lua, plugin_id.to_str()?)
+        })?
+        .bind(plugin_id)?;
+    nvim::schedule(lua, fire)
+}
+
 impl<'lua> IntoLua<'lua> for Handler<'lua> {
     fn into_lua(self, lua: &'lua Lua) -> LuaResult<LuaValue<'lua>> {
         self.0.into_lua(lua)
@@ -169,3 +428,147 @@ mod stop_funcs {
         Ok(())
     }
 }
+
+/// `registry[REGISTRY_KEY]: table<plugin_id, plugin_loader>`
+mod loaders {
+    use mlua::prelude::*;
+
+    const REGISTRY_KEY: &str = concat!(env!("CARGO_PKG_NAME"), ".loaders");
+
+    fn get_reg_value(lua: &Lua) -> LuaResult<LuaTable<'_>> {
+        if let Ok(t) = lua.named_registry_value(REGISTRY_KEY) {
+            return Ok(t);
+        }
+        let t = lua.create_table()?;
+        lua.set_named_registry_value(REGISTRY_KEY, t.clone())?;
+        Ok(t)
+    }
+
+    pub fn set<'lua>(
+        lua: &'lua Lua,
+        plugin_id: LuaString<'lua>,
+        plugin_loader: LuaFunction<'lua>,
+    ) -> LuaResult<()> {
+        get_reg_value(lua)?.raw_set(plugin_id, plugin_loader)
+    }
+
+    /// Remove the loader and return it.
+    pub fn take<'lua>(
+        lua: &'lua Lua,
+        plugin_id: LuaString<'lua>,
+    ) -> LuaResult<Option<LuaFunction<'lua>>> {
+        let reg_value = get_reg_value(lua)?;
+        let loader = reg_value.raw_get(plugin_id.clone())?;
+        reg_value.raw_set(plugin_id, LuaNil)?;
+        Ok(loader)
+    }
+
+    pub fn contains(lua: &Lua, plugin_id: &str) -> LuaResult<bool> {
+        get_reg_value(lua)?.contains_key(plugin_id)
+    }
+
+    pub fn ids(lua: &Lua) -> LuaResult<Vec<LuaString<'_>>> {
+        get_reg_value(lua)?
+            .pairs::<LuaString, LuaValue>()
+            .map(|pair| pair.map(|(id, _)| id))
+            .collect()
+    }
+}
+
+/// `registry[REGISTRY_KEY]: plugin_id[] | nil`
+///
+/// The plugins to be loaded by [`super::release_loads()`]. `nil` means
+/// that the plugins are not held.
+mod held {
+    use mlua::prelude::*;
+
+    const REGISTRY_KEY: &str = concat!(env!("CARGO_PKG_NAME"), ".held");
+
+    pub fn start(lua: &Lua) -> LuaResult<()> {
+        lua.set_named_registry_value(REGISTRY_KEY, lua.create_table()?)
+    }
+
+    /// Hold the plugin and return `true` if the plugins are held.
+    pub fn push<'lua>(lua: &'lua Lua, plugin_id: LuaString<'lua>) -> LuaResult<bool> {
+        let Some(t) = lua.named_registry_value::<Option<LuaTable>>(REGISTRY_KEY)? else {
+            return Ok(false);
+        };
+        t.raw_push(plugin_id)?;
+        Ok(true)
+    }
+
+    /// Stop holding the plugins and return the held ones.
+    pub fn take(lua: &Lua) -> LuaResult<Vec<LuaString<'_>>> {
+        let Some(t) = lua.named_registry_value::<Option<LuaTable>>(REGISTRY_KEY)? else {
+            return Ok(Vec::new());
+        };
+        lua.set_named_registry_value(REGISTRY_KEY, LuaNil)?;
+        t.sequence_values().collect()
+    }
+}
+
+/// `registry[REGISTRY_KEY]: table<path, plugin_id>`
+///
+/// The packages watched by [`super::watch_packadd()`].
+mod packages {
+    use mlua::prelude::*;
+
+    const REGISTRY_KEY: &str = concat!(env!("CARGO_PKG_NAME"), ".packages");
+
+    fn get_reg_value(lua: &Lua) -> LuaResult<LuaTable<'_>> {
+        if let Ok(t) = lua.named_registry_value(REGISTRY_KEY) {
+            return Ok(t);
+        }
+        let t = lua.create_table()?;
+        lua.set_named_registry_value(REGISTRY_KEY, t.clone())?;
+        Ok(t)
+    }
+
+    pub fn set<'lua>(
+        lua: &'lua Lua,
+        path: &str,
+        plugin_id: LuaString<'lua>,
+    ) -> LuaResult<()> {
+        get_reg_value(lua)?.raw_set(path, plugin_id)
+    }
+
+    pub fn get<'lua>(lua: &'lua Lua, path: &str) -> LuaResult<Option<LuaString<'lua>>> {
+        get_reg_value(lua)?.raw_get(path)
+    }
+
+    pub fn remove(lua: &Lua, path: &str) -> LuaResult<()> {
+        get_reg_value(lua)?.raw_set(path, LuaNil)
+    }
+
+    pub fn entries(lua: &Lua) -> LuaResult<Vec<(LuaString<'_>, LuaString<'_>)>> {
+        get_reg_value(lua)?.pairs().collect()
+    }
+
+    pub fn is_empty(lua: &Lua) -> LuaResult<bool> {
+        Ok(get_reg_value(lua)?.is_empty())
+    }
+}
+
+/// `registry[REGISTRY_KEY]: table<plugin_id, true>`
+mod loaded {
+    use mlua::prelude::*;
+
+    const REGISTRY_KEY: &str = concat!(env!("CARGO_PKG_NAME"), ".loaded");
+
+    fn get_reg_value(lua: &Lua) -> LuaResult<LuaTable<'_>> {
+        if let Ok(t) = lua.named_registry_value(REGISTRY_KEY) {
+            return Ok(t);
+        }
+        let t = lua.create_table()?;
+        lua.set_named_registry_value(REGISTRY_KEY, t.clone())?;
+        Ok(t)
+    }
+
+    pub fn set(lua: &Lua, plugin_id: LuaString) -> LuaResult<()> {
+        get_reg_value(lua)?.raw_set(plugin_id, true)
+    }
+
+    pub fn contains(lua: &Lua, plugin_id: &str) -> LuaResult<bool> {
+        get_reg_value(lua)?.contains_key(plugin_id)
+    }
+}
@@ -0,0 +1,165 @@
+use mlua::prelude::*;
+
+use super::{is_loaded, is_registered};
+
+pub struct After {
+    /// Identifier of the plugin to wait for.
+    host: String,
+    /// Identifier of the plugin subscribing to `host`, set by [`After::start()`].
+    plugin_id: Option<String>,
+}
+
+impl LuaUserData for After {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut(
+            "start",
+            |lua, this, (plugin_loader, plugin): (LuaFunction, LuaTable)| {
+                this.start(lua, plugin_loader, plugin)
+            },
+        );
+
+        methods.add_method_mut("stop", |lua, this, _: ()| this.stop(lua));
+    }
+}
+
+impl<'lua> After {
+    pub fn new(_lua: &'lua Lua, host: String) -> LuaResult<Self> {
+        let r = Self {
+            host,
+            plugin_id: None,
+        };
+        Ok(r)
+    }
+
+    fn start(
+        &mut self,
+        lua: &'lua Lua,
+        plugin_loader: LuaFunction<'lua>,
+        plugin: LuaTable<'lua>,
+    ) -> LuaResult<()> {
+        let plugin_id: String = plugin.get("id")?;
+        let host = self.host.as_str();
+
+        if is_loaded(lua, host)? {
+            return plugin_loader.call(());
+        }
+        if !is_registered(lua, host)? {
+            return Err(LuaError::runtime(format!(
+                "lazy.after: plugin `{host}` does not exist"
+            )));
+        }
+        if let Some(path) = subscribers::find_path(lua, &plugin_id, host)? {
+            return Err(LuaError::runtime(format!(
+                "lazy.after: circular dependency: {host} -> {}",
+                path.join(" -> ")
+            )));
+        }
+
+        subscribers::set(lua, host, &plugin_id, Some(plugin_loader))?;
+        self.plugin_id = Some(plugin_id);
+
+        Ok(())
+    }
+
+    fn stop(&mut self, lua: &'lua Lua) -> LuaResult<()> {
+        if let Some(plugin_id) = self.plugin_id.take() {
+            subscribers::set(lua, &self.host, &plugin_id, None)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Load the plugins waiting for `host`.
+pub fn fire(lua: &Lua, host: &str) -> LuaResult<()> {
+    for plugin_loader in subscribers::take(lua, host)? {
+        plugin_loader.call::<_, ()>(())?;
+    }
+
+    Ok(())
+}
+
+/// `registry[REGISTRY_KEY]: table<host_id, table<plugin_id, plugin_loader>>`
+mod subscribers {
+    use mlua::prelude::*;
+
+    const REGISTRY_KEY: &str = concat!(env!("CARGO_PKG_NAME"), ".after");
+
+    fn get_reg_value(lua: &Lua) -> LuaResult<LuaTable<'_>> {
+        if let Ok(t) = lua.named_registry_value(REGISTRY_KEY) {
+            return Ok(t);
+        }
+        let t = lua.create_table()?;
+        lua.set_named_registry_value(REGISTRY_KEY, t.clone())?;
+        Ok(t)
+    }
+
+    fn ids(lua: &Lua, host: &str) -> LuaResult<Vec<String>> {
+        let Some(t) = get_reg_value(lua)?.raw_get::<_, Option<LuaTable>>(host)? else {
+            return Ok(Vec::new());
+        };
+        let mut ids = t
+            .pairs::<String, LuaValue>()
+            .map(|pair| pair.map(|(id, _)| id))
+            .collect::<LuaResult<Vec<_>>>()?;
+        ids.sort();
+        Ok(ids)
+    }
+
+    pub fn set<'lua>(
+        lua: &'lua Lua,
+        host: &str,
+        plugin_id: &str,
+        plugin_loader: Option<LuaFunction<'lua>>,
+    ) -> LuaResult<()> {
+        let reg_value = get_reg_value(lua)?;
+        let t = match reg_value.raw_get::<_, Option<LuaTable>>(host)? {
+            Some(t) => t,
+            None if plugin_loader.is_none() => return Ok(()),
+            None => {
+                let t = lua.create_table()?;
+                reg_value.raw_set(host, t.clone())?;
+                t
+            }
+        };
+        t.raw_set(plugin_id, plugin_loader)
+    }
+
+    /// Remove the subscribers of `host` and return their loaders
+    /// in the order of the plugin identifiers.
+    pub fn take<'lua>(lua: &'lua Lua, host: &str) -> LuaResult<Vec<LuaFunction<'lua>>> {
+        let ids = ids(lua, host)?;
+        let reg_value = get_reg_value(lua)?;
+        let Some(t) = reg_value.raw_get::<_, Option<LuaTable>>(host)? else {
+            return Ok(Vec::new());
+        };
+        reg_value.raw_set(host, LuaNil)?;
+
+        ids.into_iter().map(|id| t.raw_get(id)).collect()
+    }
+
+    /// Find plugins through which `to` is loaded after `from`,
+    /// e.g. `[from, a, to]` for `from -> a -> to`.
+    pub fn find_path(lua: &Lua, from: &str, to: &str) -> LuaResult<Option<Vec<String>>> {
+        let mut stack = vec![vec![from.to_string()]];
+        let mut visited = vec![from.to_string()];
+
+        while let Some(path) = stack.pop() {
+            let last = path.last().unwrap();
+            if last == to {
+                return Ok(Some(path));
+            }
+            for id in ids(lua, last)? {
+                if visited.contains(&id) {
+                    continue;
+                }
+                visited.push(id.clone());
+                let mut path = path.clone();
+                path.push(id);
+                stack.push(path);
+            }
+        }
+
+        Ok(None)
+    }
+}
@@ -0,0 +1,126 @@
+use mlua::prelude::*;
+
+use super::{call_loader, value_to_vec};
+use crate::{nvim, utils::expand_value};
+
+pub struct Cmd {
+    cmd: Vec<String>,
+    /// User commands created by [`Cmd::start()`] and not deleted yet.
+    stubs: Vec<String>,
+}
+
+impl LuaUserData for Cmd {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut("start", |lua, this, plugin_loader: LuaFunction| {
+            this.start(lua, plugin_loader)
+        });
+
+        methods.add_method_mut("stop", |lua, this, _: ()| this.stop(lua));
+    }
+}
+
+impl<'lua> Cmd {
+    pub fn new(_lua: &'lua Lua, cmd: LuaValue<'lua>) -> LuaResult<Self> {
+        let r = Self {
+            cmd: value_to_vec(cmd)?,
+            stubs: Vec::new(),
+        };
+        Ok(r)
+    }
+
+    fn start(
+        &mut self,
+        lua: &'lua Lua,
+        plugin_loader: LuaFunction<'lua>,
+    ) -> LuaResult<()> {
+        for name in &self.cmd {
+            let command = lua
+                .create_function(exec_cmd)?
+                .bind((name.as_str(), plugin_loader.clone()))?;
+            let complete = lua
+                .create_function(complete_cmd)?
+                .bind(plugin_loader.clone())?;
+
+            let opts = lua.create_table()?;
+            opts.set("bang", true)?;
+            opts.set("range", true)?;
+            opts.set("nargs", "*")?;
+            opts.set("complete", complete)?;
+
+            nvim::create_user_command(lua, name.as_str(), command, opts)?;
+            self.stubs.push(name.clone());
+        }
+
+        Ok(())
+    }
+
+    fn stop(&mut self, lua: &'lua Lua) -> LuaResult<()> {
+        for name in self.stubs.drain(..) {
+            nvim::del_user_command(lua, name.as_str())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Load the plugin, then execute the command defined by it with the same
+/// bang, range, count, modifiers and arguments. Nothing is executed if
+/// the plugin is not loaded yet, since this stub would be executed again.
+fn exec_cmd(
+    lua: &Lua,
+    (name, plugin_loader, ev): (LuaString, LuaFunction, LuaTable),
+) -> LuaResult<()> {
+    expand_value!(ev, {
+        bang: bool,
+        range: LuaInteger,
+        line1: LuaInteger,
+        line2: LuaInteger,
+        count: LuaInteger,
+        smods: LuaTable,
+        args: String,
+    });
+
+    if !call_loader(&plugin_loader)? {
+        return Ok(());
+    }
+
+    let cmd = lua.create_table()?;
+    cmd.set("cmd", name)?;
+    cmd.set("mods", smods)?;
+    // Pass the arguments as typed, since the command defined by the plugin
+    // may take them differently from the stub, e.g. `-nargs=?`.
+    if !args.is_empty() {
+        cmd.set("args", [args])?;
+    }
+    if bang {
+        cmd.set("bang", true)?;
+    }
+    match range {
+        1 => cmd.set("range", [line1])?,
+        2 => cmd.set("range", [line1, line2])?,
+        _ => {
+            if count >= 0 {
+                cmd.set("count", count)?;
+            }
+        }
+    }
+
+    nvim::cmd(lua, cmd)
+}
+
+/// Load the plugin, then return the completion of the command defined by it.
+fn complete_cmd<'lua>(
+    lua: &'lua Lua,
+    (plugin_loader, _arg_lead, cmd_line, _cursor_pos): (
+        LuaFunction<'lua>,
+        LuaString<'lua>,
+        LuaString<'lua>,
+        LuaInteger,
+    ),
+) -> LuaResult<LuaTable<'lua>> {
+    if !call_loader(&plugin_loader)? {
+        return lua.create_table();
+    }
+
+    nvim::get_completion(lua, cmd_line, "cmdline")
+}
@@ -0,0 +1,61 @@
+use mlua::prelude::*;
+
+use super::{event::Event, value_to_vec};
+
+pub struct Colorscheme {
+    /// [`None`] means the colorschemes in the plugin's `colors/` directory.
+    name: Option<Vec<String>>,
+    event: Option<Event>,
+}
+
+impl LuaUserData for Colorscheme {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut(
+            "start",
+            |lua, this, (plugin_loader, plugin): (LuaFunction, LuaTable)| {
+                this.start(lua, plugin_loader, plugin)
+            },
+        );
+
+        methods.add_method_mut("stop", |lua, this, _: ()| this.stop(lua));
+    }
+}
+
+impl<'lua> Colorscheme {
+    pub fn new(_lua: &'lua Lua, name: Option<LuaValue<'lua>>) -> LuaResult<Self> {
+        let r = Self {
+            name: name.map(value_to_vec).transpose()?,
+            event: None,
+        };
+        Ok(r)
+    }
+
+    fn start(
+        &mut self,
+        lua: &'lua Lua,
+        plugin_loader: LuaFunction<'lua>,
+        plugin: LuaTable<'lua>,
+    ) -> LuaResult<()> {
+        let name = match self.name {
+            Some(ref name) => name.clone(),
+            None => plugin.get("colors")?,
+        };
+        if name.is_empty() {
+            return Ok(());
+        }
+
+        let mut event = Event::with_pattern(vec![String::from("ColorSchemePre")], name);
+        event.start(lua, plugin_loader)?;
+        self.event = Some(event);
+
+        Ok(())
+    }
+
+    fn stop(&mut self, lua: &'lua Lua) -> LuaResult<()> {
+        if let Some(mut event) = self.event.take() {
+            event.stop(lua)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,189 @@
+//! Lazy handlers composed of other lazy handlers.
+
+use std::{cell::RefCell, rc::Rc};
+
+use mlua::prelude::*;
+
+use super::{call_loader, Handler};
+
+/// Load the plugin when any of the handlers fires.
+pub struct Any {
+    handlers: LuaRegistryKey,
+}
+
+/// Load the plugin when all of the handlers have fired.
+pub struct All {
+    handlers: LuaRegistryKey,
+}
+
+/// Load the plugin when the handler fires and the predicate returns `true`.
+pub struct When {
+    pred: LuaRegistryKey,
+    handler: LuaRegistryKey,
+}
+
+impl LuaUserData for Any {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method(
+            "start",
+            |lua, this, (plugin_loader, plugin): (LuaFunction, LuaTable)| {
+                for handler in get_handlers(lua, &this.handlers)? {
+                    handler.start_with(plugin_loader.clone(), plugin.clone())?;
+                }
+                Ok(())
+            },
+        );
+
+        methods.add_method("stop", |lua, this, _: ()| {
+            for handler in get_handlers(lua, &this.handlers)? {
+                handler.stop()?;
+            }
+            Ok(())
+        });
+    }
+}
+
+impl<'lua> Any {
+    pub fn new(lua: &'lua Lua, handlers: LuaTable<'lua>) -> LuaResult<Self> {
+        Ok(Self {
+            handlers: new_handlers(lua, handlers)?,
+        })
+    }
+}
+
+impl LuaUserData for All {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method(
+            "start",
+            |lua, this, (plugin_loader, plugin): (LuaFunction, LuaTable)| {
+                let handlers = get_handlers(lua, &this.handlers)?;
+                let fired = Rc::new(RefCell::new(vec![false; handlers.len()]));
+
+                for (i, handler) in handlers.into_iter().enumerate() {
+                    let fired = fired.clone();
+                    let loader = lua
+                        .create_function(move |_lua, plugin_loader: LuaFunction| {
+                            let mut fired = fired.borrow_mut();
+                            fired[i] = true;
+                            if !fired.iter().all(|b| *b) {
+                                return Ok(false);
+                            }
+                            drop(fired);
+                            call_loader(&plugin_loader)
+                        })?
+                        .bind(plugin_loader.clone())?;
+                    handler.start_with(loader, plugin.clone())?;
+                }
+
+                Ok(())
+            },
+        );
+
+        methods.add_method("stop", |lua, this, _: ()| {
+            for handler in get_handlers(lua, &this.handlers)? {
+                handler.stop()?;
+            }
+            Ok(())
+        });
+    }
+}
+
+impl<'lua> All {
+    pub fn new(lua: &'lua Lua, handlers: LuaTable<'lua>) -> LuaResult<Self> {
+        Ok(Self {
+            handlers: new_handlers(lua, handlers)?,
+        })
+    }
+}
+
+impl LuaUserData for When {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method(
+            "start",
+            |lua, this, (plugin_loader, plugin): (LuaFunction, LuaTable)| {
+                let pred: LuaFunction = lua.registry_value(&this.pred)?;
+                let handler: LuaValue = lua.registry_value(&this.handler)?;
+                start_when(lua, (pred, handler, plugin_loader, plugin))
+            },
+        );
+
+        methods.add_method("stop", |lua, this, _: ()| {
+            let handler: Handler = lua.registry_value(&this.handler)?;
+            handler.stop()
+        });
+    }
+}
+
+impl<'lua> When {
+    pub fn new(
+        lua: &'lua Lua,
+        (pred, handler): (LuaFunction<'lua>, LuaValue<'lua>),
+    ) -> LuaResult<Self> {
+        // Fail early if it is not a handler.
+        Handler::from_lua(handler.clone(), lua)?;
+
+        Ok(Self {
+            pred: lua.create_registry_value(pred)?,
+            handler: lua.create_registry_value(handler)?,
+        })
+    }
+}
+
+fn new_handlers<'lua>(
+    lua: &'lua Lua,
+    handlers: LuaTable<'lua>,
+) -> LuaResult<LuaRegistryKey> {
+    // Fail early if some of them are not handlers.
+    for handler in handlers.clone().sequence_values::<Handler>() {
+        handler?;
+    }
+    lua.create_registry_value(handlers)
+}
+
+fn get_handlers<'lua>(
+    lua: &'lua Lua,
+    key: &LuaRegistryKey,
+) -> LuaResult<Vec<Handler<'lua>>> {
+    lua.registry_value::<LuaTable>(key)?
+        .sequence_values()
+        .collect()
+}
+
+fn start_when<'lua>(
+    lua: &'lua Lua,
+    (pred, handler, plugin_loader, plugin): (
+        LuaFunction<'lua>,
+        LuaValue<'lua>,
+        LuaFunction<'lua>,
+        LuaTable<'lua>,
+    ),
+) -> LuaResult<()> {
+    let loader = lua.create_function(load_when)?.bind((
+        pred,
+        handler.clone(),
+        plugin_loader,
+        plugin.clone(),
+    ))?;
+    Handler::from_lua(handler, lua)?.start_with(loader, plugin)
+}
+
+/// Load the plugin if the predicate returns `true`,
+/// otherwise restart the handler to wait for the next trigger.
+/// Returns whether the plugin has been loaded.
+fn load_when<'lua>(
+    lua: &'lua Lua,
+    (pred, handler, plugin_loader, plugin): (
+        LuaFunction<'lua>,
+        LuaValue<'lua>,
+        LuaFunction<'lua>,
+        LuaTable<'lua>,
+    ),
+) -> LuaResult<bool> {
+    if pred.call::<_, bool>(())? {
+        return call_loader(&plugin_loader);
+    }
+
+    Handler::from_lua(handler.clone(), lua)?.stop()?;
+    start_when(lua, (pred, handler, plugin_loader, plugin))?;
+    Ok(false)
+}
@@ -1,6 +1,9 @@
+use std::path::Path;
+
 use hashbrown::HashSet;
 use mlua::prelude::*;
 
+use super::{call_loader, value_to_vec};
 use crate::{nvim, utils::expand_value};
 
 pub struct Event {
@@ -21,46 +24,27 @@ impl LuaUserData for Event {
 
 impl<'lua> Event {
     pub fn new(
-        lua: &'lua Lua,
+        _lua: &'lua Lua,
         (event, pattern): (LuaValue<'lua>, Option<LuaValue<'lua>>),
     ) -> LuaResult<Self> {
-        let value_to_vec = |v: LuaValue| {
-            let t = match v {
-                LuaValue::Table(t) => t,
-                LuaValue::String(s) => {
-                    let t = lua.create_table()?;
-                    t.push(s)?;
-                    t
-                }
-                _ => {
-                    let err = LuaError::FromLuaConversionError {
-                        from: v.type_name(),
-                        to: "string or table",
-                        message: None,
-                    };
-                    return Err(err);
-                }
-            };
-            Ok(t.sequence_values::<String>()
-                .filter_map(|v| v.ok())
-                .collect::<Vec<_>>())
-        };
-
         let event = value_to_vec(event)?;
         let pattern = match pattern {
             Some(p) => value_to_vec(p)?,
             None => vec![String::from("*")],
         };
 
-        let r = Self {
+        Ok(Self::with_pattern(event, pattern))
+    }
+
+    pub fn with_pattern(event: Vec<String>, pattern: Vec<String>) -> Self {
+        Self {
             event,
             pattern,
             autocmd_ids: Vec::new(),
-        };
-        Ok(r)
+        }
     }
 
-    fn start(
+    pub fn start(
         &mut self,
         lua: &'lua Lua,
         plugin_loader: LuaFunction<'lua>,
@@ -77,7 +61,7 @@ impl<'lua> Event {
         Ok(())
     }
 
-    fn stop(&mut self, lua: &'lua Lua) -> LuaResult<()> {
+    pub fn stop(&mut self, lua: &'lua Lua) -> LuaResult<()> {
         if self.autocmd_ids.is_empty() {
             return Ok(());
         }
@@ -91,56 +75,160 @@ impl<'lua> Event {
     }
 }
 
-fn exec_added_autocmds(
+/// Events fired in this order for a buffer while reading a file.
+const READ_CHAIN: [&str; 3] = ["BufReadPre", "BufReadPost", "FileType"];
+/// Events fired in this order for a buffer while editing a new file.
+const NEW_FILE_CHAIN: [&str; 2] = ["BufNewFile", "FileType"];
+/// Events fired in this order for a buffer while entering a window.
+const ENTER_CHAIN: [&str; 2] = ["BufEnter", "BufWinEnter"];
+
+/// Load the plugin, then execute the autocommands added by it for the event.
+pub fn exec_added_autocmds(
     lua: &Lua,
     (plugin_loader, ev): (LuaFunction, LuaTable),
 ) -> LuaResult<()> {
     expand_value!(ev, {
         event: LuaString,
+        buf: LuaInteger,
+        file: String,
         data: LuaValue,
     });
     let event = event.to_str()?;
 
-    let mut exists_autocmds = Vec::new();
-    let mut exists_ids = HashSet::new();
-    let mut exists_groups = HashSet::new();
-    for autocmd in nvim::get_autocmds(lua, event)? {
-        let autocmd = autocmd?;
-        if let Some(id) = autocmd.id {
-            exists_ids.insert(id);
-        }
-        if let Some(group) = autocmd.group {
-            exists_groups.insert(group);
+    let Some(chain) = fired_chain(event, &file) else {
+        let pattern: String = ev.get("match")?;
+
+        let snapshot = Snapshot::new(lua, event)?;
+        if !call_loader(&plugin_loader)? {
+            return Ok(());
         }
-        exists_autocmds.push(autocmd);
+        // `match` is empty for e.g. a buffer without a name.
+        let pattern = (!pattern.is_empty()).then_some(pattern.as_str());
+        snapshot.exec_added(lua, None, pattern, data)?;
+
+        return Ok(());
+    };
+
+    let snapshots = chain
+        .iter()
+        .map(|event| Snapshot::new(lua, event))
+        .collect::<LuaResult<Vec<_>>>()?;
+    if !call_loader(&plugin_loader)? {
+        return Ok(());
     }
 
-    plugin_loader.call(())?;
+    // The events after the trigger will be fired by Neovim as usual,
+    // so only the ones the plugin has missed are replayed.
+    let Some((trigger, fired)) = snapshots.split_last() else {
+        return Ok(());
+    };
+    for snapshot in fired {
+        snapshot.exec_added(lua, Some(buf), None, LuaNil)?;
+    }
+    trigger.exec_added(lua, Some(buf), None, data)?;
+
+    Ok(())
+}
+
+/// The events of the chain which have been fired for the buffer,
+/// ending with `event`.
+fn fired_chain(event: &str, file: &str) -> Option<&'static [&'static str]> {
+    let chain: &[&str] = match event {
+        "BufReadPre" | "BufReadPost" | "BufRead" => &READ_CHAIN,
+        "BufNewFile" => &NEW_FILE_CHAIN,
+        // e.g. `:setfiletype` in a scratch buffer
+        "FileType" if file.is_empty() => return None,
+        "FileType" if Path::new(file).exists() => &READ_CHAIN,
+        "FileType" => &NEW_FILE_CHAIN,
+        "BufEnter" | "BufWinEnter" => &ENTER_CHAIN,
+        _ => return None,
+    };
+    // `BufRead` is an alias of `BufReadPost`.
+    let event = if event == "BufRead" {
+        "BufReadPost"
+    } else {
+        event
+    };
+    let i = chain.iter().position(|e| *e == event)?;
+
+    Some(&chain[..=i])
+}
+
+/// Autocommands for an event that existed before loading a plugin.
+pub struct Snapshot<'lua> {
+    event: String,
+    autocmds: Vec<nvim::AutoCommand<'lua>>,
+    ids: HashSet<LuaInteger>,
+    groups: HashSet<LuaInteger>,
+}
 
-    let mut executed_groups = HashSet::new();
-    'autocmd: for autocmd in nvim::get_autocmds(lua, event)? {
-        let autocmd = autocmd?;
-        if let Some(id) = autocmd.id {
-            if exists_ids.contains(&id) {
-                continue;
+impl<'lua> Snapshot<'lua> {
+    pub fn new(lua: &'lua Lua, event: &str) -> LuaResult<Self> {
+        let mut autocmds = Vec::new();
+        let mut ids = HashSet::new();
+        let mut groups = HashSet::new();
+        for autocmd in nvim::get_autocmds(lua, event)? {
+            let autocmd = autocmd?;
+            if let Some(id) = autocmd.id {
+                ids.insert(id);
             }
+            if let Some(group) = autocmd.group {
+                groups.insert(group);
+            }
+            autocmds.push(autocmd);
         }
-        if let Some(group) = autocmd.group {
-            if exists_groups.contains(&group) {
-                continue;
+
+        let r = Self {
+            event: event.to_string(),
+            autocmds,
+            ids,
+            groups,
+        };
+        Ok(r)
+    }
+
+    /// Execute the autocommands added after taking the snapshot.
+    pub fn exec_added(
+        &self,
+        lua: &'lua Lua,
+        buffer: Option<LuaInteger>,
+        pattern: Option<&str>,
+        data: LuaValue<'lua>,
+    ) -> LuaResult<()> {
+        let event = self.event.as_str();
+
+        let mut executed_groups = HashSet::new();
+        'autocmd: for autocmd in nvim::get_autocmds(lua, event)? {
+            let autocmd = autocmd?;
+            if let Some(id) = autocmd.id {
+                if self.ids.contains(&id) {
+                    continue;
+                }
             }
-            if executed_groups.contains(&group) {
-                continue;
+            if let Some(group) = autocmd.group {
+                if self.groups.contains(&group) {
+                    continue;
+                }
+                if executed_groups.contains(&group) {
+                    continue;
+                }
+                executed_groups.insert(group);
             }
-            executed_groups.insert(group);
-        }
-        for exists in &exists_autocmds {
-            if autocmd == *exists {
-                continue 'autocmd;
+            for exists in &self.autocmds {
+                if autocmd == *exists {
+                    continue 'autocmd;
+                }
             }
+            nvim::exec_autocmds(
+                lua,
+                event,
+                autocmd.group,
+                buffer,
+                pattern,
+                data.clone(),
+            )?;
         }
-        nvim::exec_autocmds(lua, event, autocmd.group, data.clone())?;
-    }
 
-    Ok(())
+        Ok(())
+    }
 }
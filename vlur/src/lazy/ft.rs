@@ -0,0 +1,104 @@
+use mlua::prelude::*;
+
+use super::{call_loader, event::Snapshot, value_to_vec};
+use crate::nvim;
+
+/// Autocommand groups which load the filetype-specific runtime files,
+/// i.e. `ftplugin/`, `indent/` and `syntax/`.
+const RUNTIME_GROUPS: [&str; 3] = ["filetypeplugin", "filetypeindent", "syntaxset"];
+
+pub struct Ft {
+    filetype: Vec<String>,
+    autocmd_ids: Vec<LuaInteger>,
+}
+
+impl LuaUserData for Ft {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut("start", |lua, this, plugin_loader: LuaFunction| {
+            this.start(lua, plugin_loader)
+        });
+
+        methods.add_method_mut("stop", |lua, this, _: ()| this.stop(lua));
+    }
+}
+
+impl<'lua> Ft {
+    pub fn new(_lua: &'lua Lua, filetype: LuaValue<'lua>) -> LuaResult<Self> {
+        let r = Self {
+            filetype: value_to_vec(filetype)?,
+            autocmd_ids: Vec::new(),
+        };
+        Ok(r)
+    }
+
+    fn start(
+        &mut self,
+        lua: &'lua Lua,
+        plugin_loader: LuaFunction<'lua>,
+    ) -> LuaResult<()> {
+        let pattern = self.filetype.as_slice();
+        let plugin_loader = lua.create_function(load_and_detect)?.bind(plugin_loader)?;
+
+        let id = nvim::create_autocmd(lua, "FileType", pattern, plugin_loader, true)?;
+        self.autocmd_ids.push(id);
+
+        Ok(())
+    }
+
+    fn stop(&mut self, lua: &'lua Lua) -> LuaResult<()> {
+        for id in self.autocmd_ids.drain(..) {
+            nvim::del_autocmd(lua, id)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Load the plugin, then run the filetype-specific runtime files and
+/// the added `FileType` autocommands again for every buffer of the filetype.
+fn load_and_detect(
+    lua: &Lua,
+    (plugin_loader, ev): (LuaFunction, LuaTable),
+) -> LuaResult<()> {
+    let filetype: String = ev.get("match")?;
+    let trigger: LuaInteger = ev.get("buf")?;
+
+    let snapshot = Snapshot::new(lua, "FileType")?;
+    if !call_loader(&plugin_loader)? {
+        return Ok(());
+    }
+
+    let mut runtime_groups = Vec::new();
+    for autocmd in nvim::get_autocmds(lua, "FileType")? {
+        let autocmd = autocmd?;
+        let (Some(group), Some(name)) = (autocmd.group, autocmd.group_name) else {
+            continue;
+        };
+        if RUNTIME_GROUPS.contains(&name.as_str()) && !runtime_groups.contains(&group) {
+            runtime_groups.push(group);
+        }
+    }
+
+    for buf in nvim::list_bufs(lua)? {
+        if nvim::get_buf_opt::<String>(lua, buf, "filetype")? != filetype {
+            continue;
+        }
+        // Neovim runs the runtime groups for the triggering buffer right
+        // after this autocommand, since they are defined later.
+        if buf != trigger {
+            for group in &runtime_groups {
+                nvim::exec_autocmds(
+                    lua,
+                    "FileType",
+                    Some(*group),
+                    Some(buf),
+                    None,
+                    LuaNil,
+                )?;
+            }
+        }
+        snapshot.exec_added(lua, Some(buf), None, LuaNil)?;
+    }
+
+    Ok(())
+}
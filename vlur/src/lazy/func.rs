@@ -0,0 +1,62 @@
+use mlua::prelude::*;
+
+use super::{event::Event, value_to_vec};
+
+pub struct Func {
+    /// [`None`] means the autoload functions in the plugin's `autoload/` directory.
+    pattern: Option<Vec<String>>,
+    event: Option<Event>,
+}
+
+impl LuaUserData for Func {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut(
+            "start",
+            |lua, this, (plugin_loader, plugin): (LuaFunction, LuaTable)| {
+                this.start(lua, plugin_loader, plugin)
+            },
+        );
+
+        methods.add_method_mut("stop", |lua, this, _: ()| this.stop(lua));
+    }
+}
+
+impl<'lua> Func {
+    pub fn new(_lua: &'lua Lua, pattern: Option<LuaValue<'lua>>) -> LuaResult<Self> {
+        let r = Self {
+            pattern: pattern.map(value_to_vec).transpose()?,
+            event: None,
+        };
+        Ok(r)
+    }
+
+    fn start(
+        &mut self,
+        lua: &'lua Lua,
+        plugin_loader: LuaFunction<'lua>,
+        plugin: LuaTable<'lua>,
+    ) -> LuaResult<()> {
+        let pattern = match self.pattern {
+            Some(ref pattern) => pattern.clone(),
+            None => plugin.get("autoload")?,
+        };
+        if pattern.is_empty() {
+            return Ok(());
+        }
+
+        let mut event =
+            Event::with_pattern(vec![String::from("FuncUndefined")], pattern);
+        event.start(lua, plugin_loader)?;
+        self.event = Some(event);
+
+        Ok(())
+    }
+
+    fn stop(&mut self, lua: &'lua Lua) -> LuaResult<()> {
+        if let Some(mut event) = self.event.take() {
+            event.stop(lua)?;
+        }
+
+        Ok(())
+    }
+}
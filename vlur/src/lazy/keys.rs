@@ -0,0 +1,267 @@
+use mlua::prelude::*;
+
+use super::{call_loader, value_to_vec};
+use crate::{nvim, utils::expand_value};
+
+pub struct Keys {
+    keys: Vec<Key>,
+    /// Mappings created by [`Keys::start()`] and not deleted yet.
+    stubs: Vec<Stub>,
+}
+
+struct Key {
+    lhs: String,
+    mode: Vec<String>,
+    desc: Option<String>,
+    expr: bool,
+    buffer: Option<LuaInteger>,
+}
+
+struct Stub {
+    mode: String,
+    lhs: String,
+    buffer: Option<LuaInteger>,
+}
+
+impl LuaUserData for Keys {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut("start", |lua, this, plugin_loader: LuaFunction| {
+            this.start(lua, plugin_loader)
+        });
+
+        methods.add_method_mut("stop", |lua, this, _: ()| this.stop(lua));
+    }
+}
+
+impl<'lua> Keys {
+    pub fn new(lua: &'lua Lua, keys: LuaValue<'lua>) -> LuaResult<Self> {
+        let keys = match keys {
+            LuaValue::Table(t) => {
+                t.sequence_values::<Key>().collect::<LuaResult<Vec<_>>>()?
+            }
+            value => vec![Key::from_lhs(String::from_lua(value, lua)?)],
+        };
+
+        let r = Self {
+            keys,
+            stubs: Vec::new(),
+        };
+        Ok(r)
+    }
+
+    fn start(
+        &mut self,
+        lua: &'lua Lua,
+        plugin_loader: LuaFunction<'lua>,
+    ) -> LuaResult<()> {
+        for key in &self.keys {
+            // Resolve `0` now because the current buffer may change before
+            // the stubs are deleted.
+            let buffer = match key.buffer {
+                Some(0) => Some(nvim::get_current_buf(lua)?),
+                buffer => buffer,
+            };
+            for mode in &key.mode {
+                // An expression mapping keeps the pending operator, which
+                // would be applied to the stub itself otherwise.
+                let expr = key.expr || mode == "o";
+
+                let opts = lua.create_table()?;
+                opts.set("desc", key.desc.as_deref())?;
+                opts.set("buffer", buffer)?;
+                let rhs = if expr {
+                    opts.set("expr", true)?;
+                    opts.set("remap", true)?;
+                    // The operator is cancelled before `<Plug>(vlur-keys)`,
+                    // which runs in Normal mode.
+                    let plug_mode = if mode == "o" { "n" } else { mode.as_str() };
+                    let plug_rhs = lua.create_function(feed_pending)?;
+                    let plug_opts = lua.create_table()?;
+                    nvim::set_keymap(lua, plug_mode, PLUG, plug_rhs, plug_opts)?;
+                    lua.create_function(defer_feed_keys)?.bind((
+                        key.lhs.as_str(),
+                        mode.as_str(),
+                        plugin_loader.clone(),
+                    ))?
+                } else {
+                    lua.create_function(feed_keys)?.bind((
+                        key.lhs.as_str(),
+                        LuaNil,
+                        plugin_loader.clone(),
+                    ))?
+                };
+
+                nvim::set_keymap(lua, mode.as_str(), key.lhs.as_str(), rhs, opts)?;
+                self.stubs.push(Stub {
+                    mode: mode.clone(),
+                    lhs: key.lhs.clone(),
+                    buffer,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    fn stop(&mut self, lua: &'lua Lua) -> LuaResult<()> {
+        for stub in self.stubs.drain(..) {
+            nvim::del_keymap(lua, stub.mode.as_str(), stub.lhs.as_str(), stub.buffer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Mapped to [`feed_pending()`] for the expression stubs.
+const PLUG: &str = "<Plug>(vlur-keys)";
+
+/// Load the plugin, then replay the keys so that the mapping defined by
+/// the plugin runs in the current mode. Nothing is replayed if the plugin
+/// is not loaded yet, since the keys would hit this stub again.
+///
+/// `prefix` is the register, the count and the operator taken by an
+/// expression stub, otherwise they are taken from the Vim variables.
+fn feed_keys<'lua>(
+    lua: &'lua Lua,
+    (lhs, prefix, plugin_loader): (LuaString<'lua>, Option<String>, LuaFunction<'lua>),
+) -> LuaResult<()> {
+    if !call_loader(&plugin_loader)? {
+        return Ok(());
+    }
+
+    // `<Ignore>` prevents the replayed keys from being combined with
+    // the keys typed before.
+    let mut keys = String::from("<Ignore>");
+    match prefix {
+        Some(prefix) => keys.push_str(&prefix),
+        // The count and the register typed before `lhs` have been consumed
+        // by this stub.
+        None => keys.push_str(&register_and_count(lua)?),
+    }
+    keys.push_str(lhs.to_str()?);
+    nvim::feedkeys(lua, keys.as_str(), "i")?;
+
+    Ok(())
+}
+
+/// The rhs of an expression stub. The plugin may change the text or the
+/// windows while loading, which is not allowed in the expression
+/// (`textlock`), so it is loaded by `<Plug>(vlur-keys)` returned instead.
+fn defer_feed_keys<'lua>(
+    lua: &'lua Lua,
+    (lhs, mode, plugin_loader): (LuaString<'lua>, LuaString<'lua>, LuaFunction<'lua>),
+) -> LuaResult<String> {
+    let (keys, prefix) = if mode == "o" {
+        // Cancel the operator and apply it again to the replayed keys.
+        let operator: String = nvim::get_vvar(lua, "operator")?;
+        let prefix = register_and_count(lua)? + &operator;
+        (format!("<Esc>{PLUG}"), Some(prefix))
+    } else {
+        // The count and the register are kept for `<Plug>(vlur-keys)`.
+        (PLUG.to_string(), None)
+    };
+
+    let f = lua
+        .create_function(feed_keys)?
+        .bind((lhs, prefix, plugin_loader))?;
+    pending::set(lua, f)?;
+
+    Ok(keys)
+}
+
+/// The rhs of `<Plug>(vlur-keys)`.
+fn feed_pending(lua: &Lua, _: ()) -> LuaResult<()> {
+    match pending::take(lua)? {
+        Some(f) => f.call(()),
+        None => Ok(()),
+    }
+}
+
+/// `"{register}{count}` typed before the current mapping.
+fn register_and_count(lua: &Lua) -> LuaResult<String> {
+    let mut keys = String::new();
+    let register: String = nvim::get_vvar(lua, "register")?;
+    if register != "\"" {
+        keys.push('"');
+        keys.push_str(&register);
+    }
+    let count: LuaInteger = nvim::get_vvar(lua, "count")?;
+    if count > 0 {
+        keys.push_str(&count.to_string());
+    }
+    Ok(keys)
+}
+
+/// `registry[REGISTRY_KEY]: function | nil`
+///
+/// [`feed_keys()`] bound by the expression stub typed last.
+mod pending {
+    use mlua::prelude::*;
+
+    const REGISTRY_KEY: &str = concat!(env!("CARGO_PKG_NAME"), ".keys");
+
+    pub fn set(lua: &Lua, f: LuaFunction) -> LuaResult<()> {
+        lua.set_named_registry_value(REGISTRY_KEY, f)
+    }
+
+    pub fn take(lua: &Lua) -> LuaResult<Option<LuaFunction<'_>>> {
+        let f = lua.named_registry_value(REGISTRY_KEY)?;
+        lua.set_named_registry_value(REGISTRY_KEY, LuaNil)?;
+        Ok(f)
+    }
+}
+
+impl Key {
+    fn from_lhs(lhs: String) -> Self {
+        Self {
+            lhs,
+            mode: vec![String::from("n")],
+            desc: None,
+            expr: false,
+            buffer: None,
+        }
+    }
+}
+
+impl<'lua> FromLua<'lua> for Key {
+    fn from_lua(value: LuaValue<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
+        let t = match value {
+            LuaValue::Table(t) => t,
+            value => return Ok(Self::from_lhs(String::from_lua(value, lua)?)),
+        };
+
+        let lhs: String = t.get(1)?;
+        expand_value!(t, {
+            mode: Option<LuaValue>,
+            desc: Option<String>,
+            expr: Option<bool>,
+            buffer: Option<LuaValue>,
+        });
+        let mode = match mode {
+            Some(mode) => value_to_vec(mode)?,
+            None => vec![String::from("n")],
+        };
+        let buffer = match buffer {
+            // The stub would be created only in the buffer current when the
+            // handler starts, not in each buffer.
+            Some(LuaValue::Boolean(true)) => {
+                return Err(LuaError::runtime(
+                    "lazy.keys: `buffer = true` is not supported, give a buffer number",
+                ));
+            }
+            Some(n @ (LuaValue::Integer(_) | LuaValue::Number(_))) => {
+                Some(LuaInteger::from_lua(n, lua)?)
+            }
+            _ => None,
+        };
+
+        let r = Self {
+            lhs,
+            mode,
+            desc,
+            expr: expr.unwrap_or_default(),
+            buffer,
+        };
+        Ok(r)
+    }
+}
@@ -0,0 +1,123 @@
+use mlua::prelude::*;
+
+use super::{call_loader, event::Snapshot, value_to_vec};
+use crate::{nvim, utils::expand_value};
+
+pub struct Lsp {
+    /// Client names. Empty means any client.
+    name: Vec<String>,
+    /// Keys of `server_capabilities`, e.g. `inlayHintProvider`.
+    /// All of them must be supported by the client.
+    capability: Vec<String>,
+    autocmd_ids: Vec<LuaInteger>,
+}
+
+impl LuaUserData for Lsp {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut("start", |lua, this, plugin_loader: LuaFunction| {
+            this.start(lua, plugin_loader)
+        });
+
+        methods.add_method_mut("stop", |lua, this, _: ()| this.stop(lua));
+    }
+}
+
+impl<'lua> Lsp {
+    pub fn new(_lua: &'lua Lua, opts: Option<LuaTable<'lua>>) -> LuaResult<Self> {
+        let (name, capability) = match opts {
+            Some(opts) => {
+                expand_value!(opts, {
+                    name: Option<LuaValue>,
+                    capability: Option<LuaValue>,
+                });
+                (name, capability)
+            }
+            None => (None, None),
+        };
+
+        let r = Self {
+            name: name.map(value_to_vec).transpose()?.unwrap_or_default(),
+            capability: capability
+                .map(value_to_vec)
+                .transpose()?
+                .unwrap_or_default(),
+            autocmd_ids: Vec::new(),
+        };
+        Ok(r)
+    }
+
+    fn start(
+        &mut self,
+        lua: &'lua Lua,
+        plugin_loader: LuaFunction<'lua>,
+    ) -> LuaResult<()> {
+        let plugin_loader = lua.create_function(load_if_matched)?.bind((
+            plugin_loader,
+            self.name.clone(),
+            self.capability.clone(),
+        ))?;
+
+        let id = nvim::create_autocmd(lua, "LspAttach", "*", plugin_loader, false)?;
+        self.autocmd_ids.push(id);
+
+        Ok(())
+    }
+
+    fn stop(&mut self, lua: &'lua Lua) -> LuaResult<()> {
+        for id in self.autocmd_ids.drain(..) {
+            nvim::del_autocmd(lua, id)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Load the plugin and replay the `LspAttach` event if the attached client
+/// matches, otherwise keep waiting for the next one.
+fn load_if_matched<'lua>(
+    lua: &'lua Lua,
+    (plugin_loader, name, capability, ev): (
+        LuaFunction<'lua>,
+        Vec<String>,
+        Vec<String>,
+        LuaTable<'lua>,
+    ),
+) -> LuaResult<()> {
+    let data: LuaTable = ev.get("data")?;
+    let client_id: LuaInteger = data.get("client_id")?;
+    let Some(client) = nvim::get_lsp_client(lua, client_id)? else {
+        return Ok(());
+    };
+
+    if !name.is_empty() && !name.contains(&client.get::<_, String>("name")?) {
+        return Ok(());
+    }
+    let server_capabilities: LuaTable = client.get("server_capabilities")?;
+    for key in &capability {
+        if !has_capability(server_capabilities.clone(), key)? {
+            return Ok(());
+        }
+    }
+
+    // The client may attach to a buffer which is not the current one, so
+    // replay the event for that buffer rather than for the `match` pattern.
+    let buf: LuaInteger = ev.get("buf")?;
+    let snapshot = Snapshot::new(lua, "LspAttach")?;
+    if !call_loader(&plugin_loader)? {
+        return Ok(());
+    }
+    snapshot.exec_added(lua, Some(buf), None, LuaValue::Table(data))
+}
+
+/// `key` may be a dotted path, e.g. `workspace.workspaceFolders`.
+fn has_capability(capabilities: LuaTable, key: &str) -> LuaResult<bool> {
+    let mut value = LuaValue::Table(capabilities);
+    for key in key.split('.') {
+        let LuaValue::Table(t) = value else {
+            return Ok(false);
+        };
+        value = t.get(key)?;
+    }
+
+    Ok(!matches!(value, LuaValue::Nil | LuaValue::Boolean(false)))
+}
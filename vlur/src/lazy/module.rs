@@ -0,0 +1,153 @@
+use mlua::prelude::*;
+
+use super::value_to_vec;
+
+pub struct Module {
+    /// [`None`] means the top-level modules in the plugin's `lua/` directory.
+    modname: Option<Vec<String>>,
+    /// Module names registered by [`Module::start()`] and not removed yet.
+    registered: Vec<String>,
+}
+
+impl LuaUserData for Module {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut(
+            "start",
+            |lua, this, (plugin_loader, plugin): (LuaFunction, LuaTable)| {
+                this.start(lua, plugin_loader, plugin)
+            },
+        );
+
+        methods.add_method_mut("stop", |lua, this, _: ()| this.stop(lua));
+    }
+}
+
+impl<'lua> Module {
+    pub fn new(_lua: &'lua Lua, modname: Option<LuaValue<'lua>>) -> LuaResult<Self> {
+        let r = Self {
+            modname: modname.map(value_to_vec).transpose()?,
+            registered: Vec::new(),
+        };
+        Ok(r)
+    }
+
+    fn start(
+        &mut self,
+        lua: &'lua Lua,
+        plugin_loader: LuaFunction<'lua>,
+        plugin: LuaTable<'lua>,
+    ) -> LuaResult<()> {
+        searcher::install(lua)?;
+
+        let modname = match self.modname {
+            Some(ref modname) => modname.clone(),
+            None => plugin.get("modules")?,
+        };
+        for name in modname {
+            searcher::set(lua, name.as_str(), plugin_loader.clone())?;
+            self.registered.push(name);
+        }
+
+        Ok(())
+    }
+
+    fn stop(&mut self, lua: &'lua Lua) -> LuaResult<()> {
+        for name in self.registered.drain(..) {
+            searcher::remove(lua, name.as_str())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A searcher inserted into `package.loaders`.
+///
+/// `registry[REGISTRY_KEY]: table<modname, plugin_loader>`
+mod searcher {
+    use mlua::prelude::*;
+
+    use super::super::call_loader;
+
+    const REGISTRY_KEY: &str = concat!(env!("CARGO_PKG_NAME"), ".modules");
+    const SEARCHER_KEY: &str = concat!(env!("CARGO_PKG_NAME"), ".searcher");
+
+    fn get_reg_value(lua: &Lua) -> LuaResult<LuaTable<'_>> {
+        if let Ok(t) = lua.named_registry_value(REGISTRY_KEY) {
+            return Ok(t);
+        }
+        let t = lua.create_table()?;
+        lua.set_named_registry_value(REGISTRY_KEY, t.clone())?;
+        Ok(t)
+    }
+
+    fn get_loaders(lua: &Lua) -> LuaResult<LuaTable<'_>> {
+        let package: LuaTable = lua.globals().get("package")?;
+        package.get("loaders")
+    }
+
+    /// Insert the searcher just after the `package.preload` searcher.
+    pub fn install(lua: &Lua) -> LuaResult<()> {
+        if lua
+            .named_registry_value::<Option<LuaFunction>>(SEARCHER_KEY)?
+            .is_some()
+        {
+            return Ok(());
+        }
+
+        let searcher = lua.create_function(search)?;
+        get_loaders(lua)?.raw_insert(2, searcher.clone())?;
+        lua.set_named_registry_value(SEARCHER_KEY, searcher)?;
+
+        Ok(())
+    }
+
+    pub fn set(lua: &Lua, modname: &str, plugin_loader: LuaFunction) -> LuaResult<()> {
+        get_reg_value(lua)?.raw_set(modname, plugin_loader)
+    }
+
+    pub fn remove(lua: &Lua, modname: &str) -> LuaResult<()> {
+        get_reg_value(lua)?.raw_set(modname, LuaNil)
+    }
+
+    /// If `modname` or its parent belongs to a lazy plugin, load the plugin
+    /// and then resolve `modname` with the other searchers.
+    fn search<'lua>(
+        lua: &'lua Lua,
+        modname: LuaString<'lua>,
+    ) -> LuaResult<LuaMultiValue<'lua>> {
+        let modules = get_reg_value(lua)?;
+
+        let mut prefix = modname.to_str()?;
+        let plugin_loader = loop {
+            if let Some(f) = modules.raw_get::<_, Option<LuaFunction>>(prefix)? {
+                break f;
+            }
+            let Some((parent, _)) = prefix.rsplit_once('.') else {
+                return Ok(LuaMultiValue::new());
+            };
+            prefix = parent;
+        };
+
+        // The loader may still be waiting for other triggers, in which case
+        // leave `modname` to the other searchers.
+        if !call_loader(&plugin_loader)? {
+            return Ok(LuaMultiValue::new());
+        }
+
+        // `vim.loader` may have replaced the searcher for `&runtimepath`
+        // placed before this one, so try all of them again.
+        let this: LuaFunction = lua.named_registry_value(SEARCHER_KEY)?;
+        for searcher in get_loaders(lua)?.sequence_values::<LuaFunction>() {
+            let searcher = searcher?;
+            if searcher == this {
+                continue;
+            }
+            let r: LuaMultiValue = searcher.call(modname.clone())?;
+            if let Some(LuaValue::Function(_)) = r.iter().next() {
+                return Ok(r);
+            }
+        }
+
+        Ok(LuaMultiValue::new())
+    }
+}
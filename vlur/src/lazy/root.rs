@@ -0,0 +1,112 @@
+use std::path::Path;
+
+use mlua::prelude::*;
+
+use super::value_to_vec;
+use crate::nvim;
+
+pub struct Root {
+    /// File names or glob patterns, e.g. `Cargo.toml` or `*.csproj`.
+    marker: Vec<String>,
+    autocmd_ids: Vec<LuaInteger>,
+}
+
+impl LuaUserData for Root {
+    fn add_methods<'lua, M: LuaUserDataMethods<'lua, Self>>(methods: &mut M) {
+        methods.add_method_mut("start", |lua, this, plugin_loader: LuaFunction| {
+            this.start(lua, plugin_loader)
+        });
+
+        methods.add_method_mut("stop", |lua, this, _: ()| this.stop(lua));
+    }
+}
+
+impl<'lua> Root {
+    pub fn new(_lua: &'lua Lua, marker: LuaValue<'lua>) -> LuaResult<Self> {
+        let r = Self {
+            marker: value_to_vec(marker)?,
+            autocmd_ids: Vec::new(),
+        };
+        Ok(r)
+    }
+
+    fn start(
+        &mut self,
+        lua: &'lua Lua,
+        plugin_loader: LuaFunction<'lua>,
+    ) -> LuaResult<()> {
+        let cwd = nvim::getcwd(lua)?;
+        if has_marker(lua, &self.marker, Path::new(&cwd))? {
+            return plugin_loader.call(());
+        }
+
+        let plugin_loader = lua
+            .create_function(load_if_marked)?
+            .bind((plugin_loader, self.marker.clone()))?;
+
+        let event = ["DirChanged", "BufEnter"];
+        let id = nvim::create_autocmd(lua, event, "*", plugin_loader, false)?;
+        self.autocmd_ids.push(id);
+
+        Ok(())
+    }
+
+    fn stop(&mut self, lua: &'lua Lua) -> LuaResult<()> {
+        for id in self.autocmd_ids.drain(..) {
+            nvim::del_autocmd(lua, id)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Load the plugin if the new working directory or the entered buffer's
+/// directory is in a project with the markers.
+fn load_if_marked(
+    lua: &Lua,
+    (plugin_loader, marker, ev): (LuaFunction, Vec<String>, LuaTable),
+) -> LuaResult<()> {
+    let event: String = ev.get("event")?;
+    let dir = if event == "DirChanged" {
+        // `file` is the new working directory.
+        ev.get::<_, String>("file")?
+    } else {
+        // `match` is the full path of the buffer.
+        let name: String = ev.get("match")?;
+        let Some(parent) = Path::new(&name).parent() else {
+            return Ok(());
+        };
+        parent.to_string_lossy().to_string()
+    };
+    if dir.is_empty() {
+        return Ok(());
+    }
+
+    if has_marker(lua, &marker, Path::new(&dir))? {
+        plugin_loader.call::<_, ()>(())?;
+    }
+
+    Ok(())
+}
+
+/// Whether `dir` or one of its ancestors contains any of the markers.
+fn has_marker(lua: &Lua, marker: &[String], dir: &Path) -> LuaResult<bool> {
+    for dir in dir.ancestors() {
+        for marker in marker {
+            let found = if is_glob(marker) {
+                nvim::glob_exists(lua, &dir.to_string_lossy(), marker)?
+            } else {
+                dir.join(marker).exists()
+            };
+            if found {
+                return Ok(true);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+fn is_glob(marker: &str) -> bool {
+    marker.contains(['*', '?', '[', '{'])
+}
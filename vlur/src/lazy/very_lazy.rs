@@ -0,0 +1,52 @@
+//! `User VeryLazy` event fired after startup settles.
+
+use mlua::prelude::*;
+
+use crate::nvim;
+
+pub const PATTERN: &str = "VeryLazy";
+
+/// Interval to wait while the user is typing.
+const RETRY_MS: u64 = 10;
+
+/// Set once the event is scheduled.
+const REGISTRY_KEY: &str = concat!(env!("CARGO_PKG_NAME"), ".very_lazy");
+
+/// Fire `User VeryLazy` once the UI has entered and no input is pending.
+/// It is scheduled only by the first call, e.g. of `vlur.setup()`.
+///
+/// In `--headless` mode, no UI enters, so `VimEnter` is used instead.
+pub fn schedule(lua: &Lua) -> LuaResult<()> {
+    if lua.named_registry_value::<bool>(REGISTRY_KEY)? {
+        return Ok(());
+    }
+    lua.set_named_registry_value(REGISTRY_KEY, true)?;
+
+    if nvim::vim_did_enter(lua)? {
+        return nvim::schedule(lua, lua.create_function(fire)?);
+    }
+
+    let callback = lua.create_function(on_vim_enter)?;
+    nvim::create_autocmd(lua, "VimEnter", "*", callback, true)?;
+
+    Ok(())
+}
+
+fn on_vim_enter(lua: &Lua, _: LuaValue) -> LuaResult<()> {
+    if !nvim::has_ui(lua)? {
+        return nvim::schedule(lua, lua.create_function(fire)?);
+    }
+    let callback = lua.create_function(|lua, _: LuaValue| {
+        nvim::schedule(lua, lua.create_function(fire)?)
+    })?;
+    nvim::create_autocmd(lua, "UIEnter", "*", callback, true)?;
+
+    Ok(())
+}
+
+fn fire(lua: &Lua, _: ()) -> LuaResult<()> {
+    if nvim::input_pending(lua)? {
+        return nvim::defer(lua, lua.create_function(fire)?, RETRY_MS);
+    }
+    nvim::exec_autocmds(lua, "User", None, None, Some(PATTERN), LuaNil)
+}
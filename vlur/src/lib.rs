@@ -1,3 +1,4 @@
+mod adaptive;
 mod cache;
 mod install;
 mod lazy;
@@ -5,7 +6,9 @@ mod module;
 mod nvim;
 mod plugin;
 mod runtimepath;
+mod scan;
 mod setup;
+mod stub;
 mod ui;
 mod utils;
 
@@ -1,6 +1,11 @@
 use mlua::prelude::*;
 
-use crate::{install::installers, lazy::handlers as lazy_handlers, setup::setup};
+use crate::{
+    adaptive,
+    install::{self, installers},
+    lazy::{self, handlers as lazy_handlers},
+    setup::setup,
+};
 
 pub struct Module;
 
@@ -15,6 +20,16 @@ impl LuaUserData for Module {
         fields.add_field_function_get("lazy", |lua, _| lazy_handlers(lua));
         fields.add_field_function_get("install", |lua, _| installers(lua));
         fields.add_field_function_get("setup", |lua, _| lua.create_function(setup));
+        fields.add_field_function_get("load", |lua, _| lua.create_function(lazy::load));
+        fields.add_field_function_get("load_all", |lua, _| {
+            lua.create_function(lazy::load_all)
+        });
+        fields.add_field_function_get("update", |lua, _| {
+            lua.create_function(install::update)
+        });
+        fields.add_field_function_get("deferred", |lua, _| {
+            lua.create_function(adaptive::deferred)
+        });
 
         log::trace!("loaded the Rust module");
     }
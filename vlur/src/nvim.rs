@@ -31,6 +31,11 @@ macro_rules! nvim {
     ($lua:ident . $name:ident : $ty:ty) => {
         self::load_from_lua::<$ty>($lua, stringify!($name))
     };
+    ($lua:ident . $name:ident () -> $ty:ty) => {{
+        let f = nvim!($lua . $name : ::mlua::Function);
+        let r = f.and_then(|f| f.call::<_, $ty>(()));
+        r
+    }};
     ($lua:ident . $name:ident ( $a:expr ) -> $ty:ty) => {{
         let f = nvim!($lua . $name : ::mlua::Function);
         let r = f.and_then(|f| f.call::<_, $ty>($a));
@@ -79,6 +84,10 @@ pub fn cache_dir(lua: &Lua) -> LuaResult<PathBuf> {
     Ok(Path::new(&nvim!(lua.cache_dir: String)?).join("vlur"))
 }
 
+pub fn state_dir(lua: &Lua) -> LuaResult<PathBuf> {
+    Ok(Path::new(&nvim!(lua.state_dir: String)?).join("vlur"))
+}
+
 pub fn create_autocmd<'lua, E, P>(
     lua: &'lua Lua,
     event: E,
@@ -118,19 +127,142 @@ pub fn exec_autocmds<'lua, E>(
     lua: &'lua Lua,
     event: E,
     group: Option<LuaInteger>,
+    buffer: Option<LuaInteger>,
+    pattern: Option<&str>,
     data: LuaValue,
 ) -> LuaResult<()>
 where
     E: IntoLua<'lua>,
 {
     let event = event.into_lua(lua)?;
-    nvim!(lua.exec_autocmds(event, group, data))
+    nvim!(lua.exec_autocmds(event, group, buffer, pattern, data))
+}
+
+pub fn vim_did_enter(lua: &Lua) -> LuaResult<bool> {
+    nvim!(lua.vim_did_enter() -> bool)
+}
+
+pub fn has_ui(lua: &Lua) -> LuaResult<bool> {
+    nvim!(lua.has_ui() -> bool)
+}
+
+pub fn input_pending(lua: &Lua) -> LuaResult<bool> {
+    nvim!(lua.input_pending() -> bool)
+}
+
+pub fn schedule<'lua>(lua: &'lua Lua, callback: LuaFunction<'lua>) -> LuaResult<()> {
+    nvim!(lua.schedule(callback))
+}
+
+pub fn defer<'lua>(
+    lua: &'lua Lua,
+    callback: LuaFunction<'lua>,
+    timeout: u64,
+) -> LuaResult<()> {
+    nvim!(lua.defer(callback, timeout))
+}
+
+pub fn list_bufs(lua: &Lua) -> LuaResult<Vec<LuaInteger>> {
+    nvim!(lua.list_bufs() -> Vec<LuaInteger>)
+}
+
+pub fn get_buf_opt<'lua, R>(lua: &'lua Lua, buf: LuaInteger, name: &str) -> LuaResult<R>
+where
+    R: FromLuaMulti<'lua>,
+{
+    nvim!(lua.get_buf_opt(buf, name) -> R)
+}
+
+pub fn create_user_command<'lua>(
+    lua: &'lua Lua,
+    name: &str,
+    command: LuaFunction<'lua>,
+    opts: LuaTable<'lua>,
+) -> LuaResult<()> {
+    nvim!(lua.create_user_command(name, command, opts))
+}
+
+pub fn del_user_command(lua: &Lua, name: &str) -> LuaResult<()> {
+    nvim!(lua.del_user_command(name))
+}
+
+pub fn cmd<'lua>(lua: &'lua Lua, cmd: LuaTable<'lua>) -> LuaResult<()> {
+    nvim!(lua.cmd(cmd))
+}
+
+pub fn get_completion<'lua, P>(
+    lua: &'lua Lua,
+    pat: P,
+    type_: &str,
+) -> LuaResult<LuaTable<'lua>>
+where
+    P: IntoLua<'lua>,
+{
+    nvim!(lua.get_completion(pat, type_) -> LuaTable)
+}
+
+pub fn set_keymap<'lua>(
+    lua: &'lua Lua,
+    mode: &str,
+    lhs: &str,
+    rhs: LuaFunction<'lua>,
+    opts: LuaTable<'lua>,
+) -> LuaResult<()> {
+    nvim!(lua.set_keymap(mode, lhs, rhs, opts))
+}
+
+pub fn del_keymap(
+    lua: &Lua,
+    mode: &str,
+    lhs: &str,
+    buffer: Option<LuaInteger>,
+) -> LuaResult<()> {
+    nvim!(lua.del_keymap(mode, lhs, buffer))
+}
+
+pub fn get_current_buf(lua: &Lua) -> LuaResult<LuaInteger> {
+    nvim!(lua.get_current_buf() -> LuaInteger)
+}
+
+pub fn feedkeys(lua: &Lua, keys: &str, mode: &str) -> LuaResult<()> {
+    nvim!(lua.feedkeys(keys, mode))
+}
+
+/// `vim.v[name]`
+pub fn get_vvar<'lua, R: FromLua<'lua>>(lua: &'lua Lua, name: &str) -> LuaResult<R> {
+    nvim!(lua.get_vvar(name) -> R)
+}
+
+pub fn getcwd(lua: &Lua) -> LuaResult<String> {
+    nvim!(lua.getcwd() -> String)
+}
+
+pub fn glob_exists(lua: &Lua, dir: &str, pattern: &str) -> LuaResult<bool> {
+    nvim!(lua.glob_exists(dir, pattern) -> bool)
+}
+
+pub fn get_lsp_client(lua: &Lua, id: LuaInteger) -> LuaResult<Option<LuaTable<'_>>> {
+    nvim!(lua.get_lsp_client(id) -> Option<LuaTable>)
+}
+
+pub fn detect_filetype(lua: &Lua, buf: LuaInteger) -> LuaResult<()> {
+    nvim!(lua.detect_filetype(buf))
+}
+
+pub fn helptags(lua: &Lua, dir: &Path) -> LuaResult<()> {
+    nvim!(lua.helptags(dir.to_str()))
+}
+
+/// `level` is the name of `vim.log.levels`, e.g. `WARN`.
+pub fn notify(lua: &Lua, msg: &str, level: &str) -> LuaResult<()> {
+    nvim!(lua.notify(msg, level))
 }
 
 #[derive(PartialEq)]
 pub struct AutoCommand<'lua> {
     pub id: Option<LuaInteger>,
     pub group: Option<LuaInteger>,
+    pub group_name: Option<String>,
     pub callback: LuaValue<'lua>,
 }
 
@@ -140,6 +272,7 @@ impl<'lua> FromLua<'lua> for AutoCommand<'lua> {
         expand_value!(value, {
             id: Option<LuaInteger>,
             group: Option<LuaInteger>,
+            group_name: Option<String>,
             command: mlua::String,
             callback: Option<LuaValue>,
         });
@@ -147,6 +280,7 @@ impl<'lua> FromLua<'lua> for AutoCommand<'lua> {
         let autocmd = Self {
             id,
             group,
+            group_name,
             callback: callback.unwrap_or(command.into_lua(lua)?),
         };
 
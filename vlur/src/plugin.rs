@@ -5,27 +5,62 @@ use mlua::prelude::*;
 use walkdir::WalkDir;
 
 use crate::{
-    cache, install::Installer, lazy::Handler as LazyHandler, nvim,
-    runtimepath::RuntimePath, utils::expand_value,
+    cache,
+    install::Installer,
+    lazy::{self, Handler as LazyHandler},
+    nvim,
+    runtimepath::RuntimePath,
+    scan::scan,
+    utils::expand_value,
 };
 
 pub struct Plugin<'lua> {
     path: PathBuf,
-    lazy: Option<LuaTable<'lua>>,
+    /// Name of the optional package in `&packpath`, which is resolved to `path`.
+    package: Option<String>,
+    lazy: Option<Lazy<'lua>>,
     install: Option<Installer<'lua>>,
 }
 
+enum Lazy<'lua> {
+    Handlers(LuaTable<'lua>),
+    /// Find the triggers from the plugin's files.
+    Auto,
+    /// Load at startup unless it was too slow to source last time.
+    Adaptive,
+}
+
+impl<'lua> FromLua<'lua> for Lazy<'lua> {
+    fn from_lua(value: LuaValue<'lua>, _lua: &'lua Lua) -> LuaResult<Self> {
+        match value {
+            LuaValue::Table(t) => Ok(Self::Handlers(t)),
+            LuaValue::String(ref s) if s.to_str()? == "auto" => Ok(Self::Auto),
+            LuaValue::String(ref s) if s.to_str()? == "adaptive" => Ok(Self::Adaptive),
+            _ => Err(LuaError::FromLuaConversionError {
+                from: value.type_name(),
+                to: "table, \"auto\" or \"adaptive\"",
+                message: None,
+            }),
+        }
+    }
+}
+
 impl<'lua> FromLua<'lua> for Plugin<'lua> {
     fn from_lua(value: LuaValue<'lua>, lua: &'lua Lua) -> LuaResult<Self> {
         let table = LuaTable::from_lua(value, lua)?;
 
         expand_value!(table, {
-            path: String,
-            lazy: Option<LuaTable>,
+            path: Option<String>,
+            package: Option<String>,
+            lazy: Option<Lazy>,
             install: Option<Installer>,
         });
+        if path.is_none() && package.is_none() {
+            return Err(LuaError::runtime("either `path` or `package` is required"));
+        }
         let r = Self {
-            path: PathBuf::from(path),
+            path: PathBuf::from(path.unwrap_or_default()),
+            package,
             lazy,
             install,
         };
@@ -35,11 +70,31 @@ impl<'lua> FromLua<'lua> for Plugin<'lua> {
 }
 
 impl<'lua> Plugin<'lua> {
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    #[inline]
+    pub fn package(&self) -> Option<&str> {
+        self.package.as_deref()
+    }
+
+    #[inline]
+    pub fn is_adaptive(&self) -> bool {
+        matches!(self.lazy, Some(Lazy::Adaptive))
+    }
+
+    #[inline]
+    pub fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
     pub fn add_to_rtp(&self, runtimepath: &mut RuntimePath, cache: &mut cache::Cache) {
         if cache.is_valid {
             if let Some(rtp) = cache.inner.runtimepaths.get(self.path.to_str().unwrap())
             {
-                *runtimepath += &rtp;
+                *runtimepath += rtp;
                 return;
             }
         }
@@ -54,11 +109,141 @@ impl<'lua> Plugin<'lua> {
             .insert(self.path.to_str().unwrap().to_string(), rtp);
     }
 
-    #[inline]
+    pub fn get_colors(&self, cache: &mut cache::Cache) -> Vec<String> {
+        let path = self.path.to_str().unwrap();
+        if cache.is_valid {
+            if let Some(colors) = cache.inner.colors.get(path) {
+                return colors.clone();
+            }
+        }
+
+        let colors = get_colors(&self.path);
+
+        cache.is_valid = false;
+        cache.inner.colors.insert(path.to_string(), colors.clone());
+
+        colors
+    }
+
+    pub fn get_lua_modules(&self, cache: &mut cache::Cache) -> Vec<String> {
+        let path = self.path.to_str().unwrap();
+        if cache.is_valid {
+            if let Some(modules) = cache.inner.modules.get(path) {
+                return modules.clone();
+            }
+        }
+
+        let modules = get_lua_modules(&self.path);
+
+        cache.is_valid = false;
+        cache
+            .inner
+            .modules
+            .insert(path.to_string(), modules.clone());
+
+        modules
+    }
+
+    pub fn get_autoload_patterns(&self, cache: &mut cache::Cache) -> Vec<String> {
+        let path = self.path.to_str().unwrap();
+        if cache.is_valid {
+            if let Some(patterns) = cache.inner.autoload.get(path) {
+                return patterns.clone();
+            }
+        }
+
+        let patterns = get_autoload_patterns(&self.path);
+
+        cache.is_valid = false;
+        cache
+            .inner
+            .autoload
+            .insert(path.to_string(), patterns.clone());
+
+        patterns
+    }
+
+    /// Source `{path}/ftdetect/*` at startup, so that the filetypes are
+    /// detected before the lazy plugin is loaded.
+    pub fn load_ftdetect(
+        &self,
+        lua: &'lua Lua,
+        cache: &mut cache::Cache,
+    ) -> LuaResult<()> {
+        let path = self.path.to_str().unwrap();
+        let files = if let (true, Some(files)) =
+            (cache.is_valid, cache.inner.ftdetect.get(path))
+        {
+            files
+        } else {
+            let files = get_ftdetect_files(&self.path);
+            cache.is_valid = false;
+            cache.inner.ftdetect.insert(path.to_string(), files);
+            cache.inner.ftdetect.get(path).unwrap()
+        };
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        nvim::exec(lua, "augroup filetypedetect")?;
+        files.iter().for_each(|file| {
+            if file.loader.load(lua).is_err() {
+                error!("failed to load the file");
+            }
+        });
+        nvim::exec(lua, "augroup END")?;
+
+        Ok(())
+    }
+
     pub fn get_lazy_handlers(
         &self,
-    ) -> Option<LuaTableSequence<'lua, LazyHandler<'lua>>> {
-        self.lazy.clone().map(|t| t.sequence_values())
+        lua: &'lua Lua,
+        cache: &mut cache::Cache,
+    ) -> LuaResult<Option<LuaTableSequence<'lua, LazyHandler<'lua>>>> {
+        let handlers = match self.lazy {
+            // Deferred by the caller if necessary.
+            None | Some(Lazy::Adaptive) => return Ok(None),
+            Some(Lazy::Handlers(ref t)) => t.clone(),
+            Some(Lazy::Auto) => {
+                let handlers = lazy::auto_handlers(lua, &self.get_triggers(cache))?;
+                if handlers.raw_len() == 0 {
+                    // Nothing would load it.
+                    let msg = format!(
+                        "vlur: no triggers are found in {}, so it is loaded at startup",
+                        self.path.display()
+                    );
+                    nvim::notify(lua, &msg, "WARN")?;
+                    return Ok(None);
+                }
+                handlers
+            }
+        };
+        Ok(Some(handlers.sequence_values()))
+    }
+
+    fn get_triggers(&self, cache: &mut cache::Cache) -> cache::Triggers {
+        let path = self.path.to_str().unwrap();
+        if cache.is_valid {
+            if let Some(triggers) = cache.inner.triggers.get(path) {
+                return triggers.clone();
+            }
+        }
+
+        let triggers = scan(&self.path);
+
+        cache.is_valid = false;
+        cache
+            .inner
+            .triggers
+            .insert(path.to_string(), triggers.clone());
+
+        triggers
+    }
+
+    #[inline]
+    pub fn installer(&self) -> Option<&Installer<'lua>> {
+        self.install.as_ref()
     }
 
     pub fn setup_installer(&self) -> LuaResult<Option<&Installer<'lua>>> {
@@ -71,6 +256,25 @@ impl<'lua> Plugin<'lua> {
         Ok(None)
     }
 
+    /// Information about the plugin passed to lazy handlers.
+    pub fn get_info(
+        &self,
+        lua: &'lua Lua,
+        id: LuaString<'lua>,
+        colors: &[String],
+        cache: &mut cache::Cache,
+    ) -> LuaResult<LuaTable<'lua>> {
+        let t = lua.create_table()?;
+
+        t.set("id", id)?;
+        t.set("path", self.path.to_str())?;
+        t.set("colors", colors)?;
+        t.set("modules", self.get_lua_modules(cache))?;
+        t.set("autoload", self.get_autoload_patterns(cache))?;
+
+        Ok(t)
+    }
+
     pub fn get_loader(&self, lua: &'lua Lua) -> LuaResult<LuaFunction<'lua>> {
         let path = self.path.clone();
 
@@ -79,17 +283,29 @@ impl<'lua> Plugin<'lua> {
             global_rtp += &get_rtp(&path);
             nvim::set_opt(lua, "runtimepath", &global_rtp)?;
 
+            // Same order as `:packadd`, except for `ftdetect/`.
             let plugin_files = get_plugin_files(&path);
-            let ftdetect_files = get_ftdetect_files(&path);
+            let after_plugin_files = get_plugin_files(&path.join("after"));
             plugin_files
                 .into_iter()
-                .chain(ftdetect_files)
+                .chain(after_plugin_files)
                 .for_each(|file| {
                     if file.loader.load(lua).is_err() {
                         error!("failed to load the file");
                     }
                 });
 
+            // `ftdetect/` has already been sourced by [`Plugin::load_ftdetect()`].
+
+            // Buffers opened before loading may be detected by the plugin.
+            for buf in nvim::list_bufs(lua)? {
+                if nvim::get_buf_opt::<String>(lua, buf, "filetype")?.is_empty()
+                    && nvim::get_buf_opt::<String>(lua, buf, "buftype")?.is_empty()
+                {
+                    nvim::detect_filetype(lua, buf)?;
+                }
+            }
+
             Ok(())
         };
 
@@ -185,6 +401,171 @@ pub fn get_ftdetect_files(dir: &Path) -> Vec<cache::File> {
     r
 }
 
+/// Optional packages in `{dir}/pack/*/opt/*`, which are not added to `&runtimepath`
+/// until `:packadd`.
+pub fn get_opt_packages(dir: &Path) -> Vec<(String, String)> {
+    let Ok(entries) = dir.join("pack").read_dir() else {
+        return Vec::new();
+    };
+
+    let mut r = Vec::new();
+
+    for entry in entries {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let Ok(packages) = entry.path().join("opt").read_dir() else {
+            continue;
+        };
+        for package in packages {
+            let Ok(package) = package else {
+                continue;
+            };
+            let path = package.path();
+            if !path.is_dir() {
+                continue;
+            }
+            let (Some(name), Some(path)) = (path.file_name(), path.to_str()) else {
+                continue;
+            };
+            let Some(name) = name.to_str() else {
+                continue;
+            };
+            r.push((name.to_string(), path.to_string()));
+        }
+    }
+
+    r
+}
+
+/// Names of the colorschemes, i.e. `{dir}/colors/{name}.{vim,lua}`
+pub fn get_colors(dir: &Path) -> Vec<String> {
+    let Ok(entries) = dir.join("colors").read_dir() else {
+        return Vec::new();
+    };
+
+    let mut r = Vec::new();
+
+    for entry in entries {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        if !path.is_file() || !is_vim_or_lua(&path) {
+            continue;
+        }
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if !r.iter().any(|n| n == name) {
+            r.push(name.to_string());
+        }
+    }
+
+    r
+}
+
+/// Patterns of the autoload functions, e.g. `foo#bar#*` for `{dir}/autoload/foo/bar.vim`
+pub fn get_autoload_patterns(dir: &Path) -> Vec<String> {
+    let dir = dir.join("autoload");
+    if !dir.exists() {
+        return Vec::new();
+    }
+
+    let entries = WalkDir::new(&dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| {
+            if entry.file_type().is_dir() {
+                return true;
+            }
+            entry.path().extension().is_some_and(|ext| ext == "vim")
+        });
+
+    let mut r = Vec::new();
+
+    for entry in entries {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if entry.file_type().is_dir() {
+            continue;
+        }
+        let Some(rel_path) = entry.path().strip_prefix(&dir).ok() else {
+            continue;
+        };
+        let names = rel_path
+            .with_extension("")
+            .components()
+            .filter_map(|c| c.as_os_str().to_str().map(|s| s.to_string()))
+            .collect::<Vec<_>>();
+        r.push(format!("{}#*", names.join("#")));
+    }
+
+    r
+}
+
+/// Names of the top-level Lua modules.
+///
+/// - `{dir}/lua/{name}.lua`
+/// - `{dir}/lua/{name}/`
+pub fn get_lua_modules(dir: &Path) -> Vec<String> {
+    let Ok(entries) = dir.join("lua").read_dir() else {
+        return Vec::new();
+    };
+
+    let mut r = Vec::new();
+
+    for entry in entries {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        let name = if path.is_dir() {
+            path.file_name()
+        } else if path.extension().is_some_and(|ext| ext == "lua") {
+            path.file_stem()
+        } else {
+            None
+        };
+        if let Some(name) = name.and_then(|s| s.to_str()) {
+            r.push(name.to_string());
+        }
+    }
+
+    r
+}
+
+/// Health check modules for `:checkhealth`, relative to `dir`.
+///
+/// - `{dir}/lua/**/health.lua`
+/// - `{dir}/lua/**/health/init.lua`
+pub fn get_health_files(dir: &Path) -> Vec<PathBuf> {
+    let lua_dir = dir.join("lua");
+    if !lua_dir.exists() {
+        return Vec::new();
+    }
+
+    let mut r = Vec::new();
+
+    for entry in WalkDir::new(lua_dir).min_depth(1) {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        let is_health =
+            path.ends_with("health.lua") || path.ends_with("health/init.lua");
+        if !is_health || !entry.file_type().is_file() {
+            continue;
+        }
+        if let Ok(rel_path) = path.strip_prefix(dir) {
+            r.push(rel_path.to_path_buf());
+        }
+    }
+
+    r
+}
+
 fn is_vim_or_lua(path: &Path) -> bool {
     let Some(path) = path.to_str() else {
         return false;
@@ -179,7 +179,7 @@ impl<'lua> FromLua<'lua> for RuntimePath {
     }
 }
 
-impl<'a, 'lua> IntoLua<'lua> for &'a RuntimePath {
+impl<'lua> IntoLua<'lua> for &RuntimePath {
     fn into_lua(self, lua: &'lua Lua) -> mlua::Result<mlua::Value<'lua>> {
         self.rtp.as_str().into_lua(lua)
     }
@@ -0,0 +1,181 @@
+//! Static analysis of the plugin's files to find the triggers for `lazy = 'auto'`.
+
+use std::{fs, path::Path};
+
+use walkdir::WalkDir;
+
+use crate::{cache::Triggers, plugin::get_autoload_patterns};
+
+/// Map arguments which can be placed before `{lhs}`.
+const MAP_ARGS: [&str; 7] = [
+    "<silent>",
+    "<nowait>",
+    "<expr>",
+    "<unique>",
+    "<script>",
+    "<special>",
+    "<remap>",
+];
+
+/// Find the user commands and the `<Plug>` mappings defined in
+/// `{dir}/plugin/**/*.vim`, and the autoload functions in `{dir}/autoload/`.
+pub fn scan(dir: &Path) -> Triggers {
+    let mut triggers = Triggers {
+        funcs: get_autoload_patterns(dir),
+        ..Default::default()
+    };
+
+    let plugin_dir = dir.join("plugin");
+    if !plugin_dir.exists() {
+        return triggers;
+    }
+
+    for entry in WalkDir::new(plugin_dir).min_depth(1) {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        if path.extension().is_none_or(|ext| ext != "vim") {
+            continue;
+        }
+        let Ok(script) = fs::read_to_string(path) else {
+            continue;
+        };
+        scan_script(&script, &mut triggers);
+    }
+
+    triggers
+}
+
+fn scan_script(script: &str, triggers: &mut Triggers) {
+    for line in script.lines() {
+        let line = line.trim_start().trim_start_matches(':');
+        if line.starts_with('"') {
+            continue;
+        }
+        if let Some(cmd) = parse_command(line) {
+            if !triggers.cmds.contains(&cmd) {
+                triggers.cmds.push(cmd);
+            }
+        } else if let Some(key) = parse_plug_mapping(line) {
+            if !triggers.keys.contains(&key) {
+                triggers.keys.push(key);
+            }
+        }
+    }
+}
+
+/// `command[!] [{attr}...] {cmd} {repl}`
+fn parse_command(line: &str) -> Option<String> {
+    let mut words = line.split_whitespace();
+    let cmd = words.next()?;
+    let cmd = cmd.strip_suffix('!').unwrap_or(cmd);
+    // `:com` is the shortest abbreviation of `:command`.
+    if cmd.len() < 3 || !"command".starts_with(cmd) {
+        return None;
+    }
+
+    let name = words.find(|word| !word.starts_with('-'))?;
+    if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return None;
+    }
+
+    Some(name.to_string())
+}
+
+/// `{mode}[nore]map[!] [{args}...] <Plug>{lhs} {rhs}`, returns `(mode, lhs)`.
+fn parse_plug_mapping(line: &str) -> Option<(String, String)> {
+    let mut words = line.split_whitespace();
+    let cmd = words.next()?;
+    let (cmd, bang) = match cmd.strip_suffix('!') {
+        Some(cmd) => (cmd, true),
+        None => (cmd, false),
+    };
+    let mode = cmd.strip_suffix("map")?;
+    let mode = mode.strip_suffix("nore").unwrap_or(mode);
+    let mode = match (mode, bang) {
+        ("", true) => "!",
+        ("", false) => "",
+        (mode, false) if mode.len() == 1 && "nvxsoilct".contains(mode) => mode,
+        _ => return None,
+    };
+
+    let lhs = words.find(|word| !MAP_ARGS.contains(&word.to_lowercase().as_str()))?;
+    // Buffer-local mappings cannot be created in advance.
+    if lhs.eq_ignore_ascii_case("<buffer>") {
+        return None;
+    }
+    if !lhs.to_lowercase().starts_with("<plug>") {
+        return None;
+    }
+
+    Some((mode.to_string(), lhs.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command() {
+        assert_eq!(parse_command("command! Foo echo"), Some("Foo".into()));
+        assert_eq!(
+            parse_command("com -nargs=* -bang -complete=file FooBar call s:foo()"),
+            Some("FooBar".into())
+        );
+        assert_eq!(parse_command("command -bar"), None);
+        assert_eq!(parse_command("co Foo"), None);
+        assert_eq!(parse_command("comclear"), None);
+        assert_eq!(parse_command("command! -nargs=1 foo"), None);
+        assert_eq!(parse_command("let g:foo = 1"), None);
+    }
+
+    #[test]
+    fn plug_mapping() {
+        assert_eq!(
+            parse_plug_mapping("nnoremap <silent> <Plug>(foo) :call foo#bar()<CR>"),
+            Some(("n".into(), "<Plug>(foo)".into()))
+        );
+        assert_eq!(
+            parse_plug_mapping("xmap <Plug>FooBar <SID>bar"),
+            Some(("x".into(), "<Plug>FooBar".into()))
+        );
+        assert_eq!(
+            parse_plug_mapping("noremap <plug>(foo) <Nop>"),
+            Some(("".into(), "<plug>(foo)".into()))
+        );
+        assert_eq!(
+            parse_plug_mapping("map! <Plug>(foo) bar"),
+            Some(("!".into(), "<Plug>(foo)".into()))
+        );
+        assert_eq!(parse_plug_mapping("nmap <buffer> <Plug>(foo) bar"), None);
+        assert_eq!(parse_plug_mapping("nmap gx <Plug>(foo)"), None);
+        assert_eq!(parse_plug_mapping("nunmap <Plug>(foo)"), None);
+        assert_eq!(parse_plug_mapping("imap! <Plug>(foo) bar"), None);
+    }
+
+    #[test]
+    fn script() {
+        let mut triggers = Triggers::default();
+        scan_script(
+            r#"
+" command! Comment
+if exists('g:loaded_foo')
+  finish
+endif
+command! -nargs=? Foo call foo#run(<q-args>)
+  :command! Foo call foo#run()
+nnoremap <silent> <Plug>(foo) :<C-u>Foo<CR>
+if !hasmapto('<Plug>(foo)')
+  nmap gf <Plug>(foo)
+endif
+"#,
+            &mut triggers,
+        );
+        assert_eq!(triggers.cmds, vec![String::from("Foo")]);
+        assert_eq!(
+            triggers.keys,
+            vec![(String::from("n"), String::from("<Plug>(foo)"))]
+        );
+    }
+}
@@ -1,20 +1,31 @@
-use std::path::Path;
+use std::{
+    env,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
+use hashbrown::HashMap;
 use log::{error, trace};
 use mlua::prelude::*;
 
 use crate::{
+    adaptive::{self, LoadTimes},
     cache::Cache,
-    install::install,
+    install::{self, install},
+    lazy::{self, schedule_very_lazy},
     nvim,
-    plugin::{get_plugin_files, Plugin},
+    plugin::{get_opt_packages, get_plugin_files, Plugin},
     runtimepath::RuntimePath,
+    stub,
+    utils::error_message,
 };
 
 pub fn setup(lua: &Lua, (plugins, config): (LuaTable, LuaTable)) -> LuaResult<()> {
     trace!("start");
 
-    let cache_file = nvim::cache_dir(lua)?.join("cache");
+    let cache_file = Cache::path(lua)?;
+    let stub_dir = nvim::cache_dir(lua)?.join("runtime");
+    let load_time_file = nvim::state_dir(lua)?.join("load_time");
 
     // :set noloadplugins
     nvim::set_opt(lua, "loadplugins", false)?;
@@ -24,39 +35,104 @@ pub fn setup(lua: &Lua, (plugins, config): (LuaTable, LuaTable)) -> LuaResult<()
 
     let mut global_rtp: RuntimePath = nvim::get_opt(lua, "runtimepath")?;
 
+    trace!("read the &packpath");
+    read_packpath(lua, &mut cache)?;
+
     trace!("read plugins");
     let mut installers = Vec::new();
+    let mut all_installers = Vec::new();
     let plugins = plugins
         .pairs::<LuaString, Plugin>()
         .filter_map(|pair| pair.ok())
-        .fold(Vec::new(), |mut plugins, (id, plugin)| {
+        .fold(Vec::new(), |mut plugins, (id, mut plugin)| {
+            // The path is needed by the installer.
+            if let Some(name) = plugin.package() {
+                let install = plugin.installer().is_some();
+                let Some(path) = package_path(&cache, name, install) else {
+                    error!("the package `{name}` is not found in &packpath");
+                    return plugins;
+                };
+                plugin.set_path(path);
+            }
+            let id_str = id.to_string_lossy().to_string();
             if let Some(installer) = plugin.setup_installer().unwrap_or_default() {
-                installers.push(installer.clone());
+                installers.push((id_str.clone(), installer.clone()));
+            }
+            if let Some(installer) = plugin.installer() {
+                all_installers.push((id_str, installer.clone()));
             }
             plugins.push((id, plugin));
             plugins
         });
 
     trace!("install plugins");
-    install(installers, 5)?;
+    install(lua, installers, install::CONCURRENCY)?;
+    install::register(lua, all_installers)?;
+
+    let budget = config
+        .get::<_, Option<f64>>("adaptive_budget")?
+        .unwrap_or(adaptive::DEFAULT_BUDGET_MS);
+    let budget = Duration::from_secs_f64(budget.max(0.0) / 1000.0);
+    let mut load_times = LoadTimes::read(&load_time_file);
+    // The key is the directory in `&runtimepath`, and the value is
+    // the adaptive plugin's directory which it belongs to.
+    let mut measured_dirs = HashMap::new();
 
     trace!("load plugins");
+    let mut lazy_plugins = Vec::new();
+    let mut lazy_colors = Vec::new();
+    let mut lazy_dirs = Vec::new();
     for (id, plugin) in plugins {
-        let Some(lazy_handlers) = plugin.get_lazy_handlers() else {
+        let path = plugin.path().to_str().unwrap().to_string();
+        let last_time = load_times.get(&path);
+        let deferred = plugin.is_adaptive() && last_time.is_some_and(|t| t > budget);
+
+        let lazy_handlers = if deferred {
+            adaptive::defer(lua, id.to_str()?, last_time.unwrap())?;
+            Some(lazy::adaptive_handlers(lua)?.sequence_values())
+        } else {
+            plugin.get_lazy_handlers(lua, &mut cache)?
+        };
+        // Adaptive plugins always have the stubs, since whether they are
+        // deferred or not may change without invalidating the cache.
+        let colors = if lazy_handlers.is_some() || plugin.is_adaptive() {
+            let colors = plugin.get_colors(&mut cache);
+            lazy_colors.extend(colors.clone());
+            lazy_dirs.push((id.to_str()?.to_string(), plugin.path().to_path_buf()));
+            colors
+        } else {
+            Vec::new()
+        };
+
+        let Some(lazy_handlers) = lazy_handlers else {
+            if plugin.is_adaptive() {
+                let after_path = plugin.path().join("after");
+                measured_dirs.insert(path.clone(), path.clone());
+                measured_dirs
+                    .insert(after_path.to_str().unwrap().to_string(), path.clone());
+                load_times.reset(&path);
+            }
             plugin.add_to_rtp(&mut global_rtp, &mut cache);
+            lazy::set_loaded(lua, id)?;
             continue;
         };
 
-        let loader = plugin.get_loader(lua)?;
+        let info = plugin.get_info(lua, id.clone(), &colors, &mut cache)?;
 
-        for handler in lazy_handlers {
-            let mut handler = handler?;
-            handler.start(lua, id.clone(), loader.clone())?;
+        let mut plugin_loader = plugin.get_loader(lua)?;
+        if deferred {
+            let file = load_time_file.clone();
+            plugin_loader = adaptive::timed_loader(lua, plugin_loader, file, path)?;
         }
+        lazy::register(lua, id.clone(), plugin_loader)?;
+        if plugin.package().is_some() {
+            lazy::watch_packadd(lua, id.clone(), plugin.path())?;
+        }
+        lazy_plugins.push((id, plugin, info, lazy_handlers));
     }
 
     trace!("load the &packpath");
-    global_rtp += get_rtp_in_packpath(lua, &mut cache)?;
+    global_rtp += &cache.inner.package.runtimepath;
 
     // Current `&runtimepath`:
     //
@@ -66,10 +142,39 @@ pub fn setup(lua: &Lua, (plugins, config): (LuaTable, LuaTable)) -> LuaResult<()
     // 4. after plugins in `&runtimepath`
     // 5. after plugins specified by user
     // 6. after plugins in start packages
+    // 7. stubs for lazy plugins
+
+    if !lazy_dirs.is_empty() {
+        global_rtp.push(stub_dir.to_str().unwrap(), true);
+    }
 
     // Update `&runtimepath`.
     nvim::set_opt(lua, "runtimepath", &global_rtp)?;
 
+    // Start the handlers after registering all lazy plugins and updating
+    // `&runtimepath`. The plugins loaded as soon as their handlers start
+    // are held until the files in `&runtimepath` are sourced, so that e.g.
+    // `lazy.after` on a plugin which is not lazy is loaded after it.
+    trace!("start lazy handlers");
+    lazy::hold_loads(lua)?;
+    for (id, plugin, info, lazy_handlers) in lazy_plugins {
+        plugin.load_ftdetect(lua, &mut cache)?;
+        for handler in lazy_handlers {
+            let result = handler
+                .and_then(|mut handler| handler.start(lua, id.clone(), info.clone()));
+            if let Err(e) = result {
+                let msg = format!(
+                    "vlur: failed to start the lazy handlers of {}: {}",
+                    id.to_string_lossy(),
+                    error_message(&e)
+                );
+                nvim::notify(lua, &msg, "ERROR")?;
+                lazy::stop_handlers(lua, id.clone())?;
+                break;
+            }
+        }
+    }
+
     let plugins_filter = config.get::<_, LuaTable>("default_plugins").ok();
     let use_filter = plugins_filter.is_some();
 
@@ -77,6 +182,7 @@ pub fn setup(lua: &Lua, (plugins, config): (LuaTable, LuaTable)) -> LuaResult<()
     for dir in &global_rtp {
         let path = Path::new(dir);
         let plugins_filter = plugins_filter.as_ref();
+        let start = Instant::now();
 
         let files = if let (true, Some(files)) =
             (cache.is_valid, cache.inner.plugins.get(dir))
@@ -109,8 +215,40 @@ pub fn setup(lua: &Lua, (plugins, config): (LuaTable, LuaTable)) -> LuaResult<()
                     error!("failed to load the file");
                 }
             });
+
+        if let Some(plugin_dir) = measured_dirs.get(dir) {
+            load_times.add(plugin_dir, start.elapsed());
+        }
+    }
+
+    trace!("load the plugins held by lazy handlers");
+    lazy::release_loads(lua)?;
+
+    if !measured_dirs.is_empty() && load_times.write(&load_time_file).is_err() {
+        error!("failed to write the load time");
+    }
+
+    if !cache.is_valid {
+        trace!("update the stubs");
+        if stub::write_colors(&stub_dir, &lazy_colors).is_err() {
+            error!("failed to write the stubs for colorschemes");
+        }
+        for (_, dir) in &lazy_dirs {
+            let doc_dir = dir.join("doc");
+            if doc_dir.is_dir() && !doc_dir.join("tags").exists() {
+                nvim::helptags(lua, &doc_dir)?;
+            }
+        }
+        if stub::write_help_tags(&stub_dir, &lazy_dirs).is_err() {
+            error!("failed to write the help tags");
+        }
+        if stub::write_health(&stub_dir, &lazy_dirs).is_err() {
+            error!("failed to write the stubs for health checks");
+        }
     }
 
+    schedule_very_lazy(lua)?;
+
     trace!("update the cache");
     cache.update(&cache_file).ok();
 
@@ -119,21 +257,49 @@ pub fn setup(lua: &Lua, (plugins, config): (LuaTable, LuaTable)) -> LuaResult<()
     Ok(())
 }
 
-fn get_rtp_in_packpath<'a>(
-    lua: &Lua,
-    cache: &'a mut Cache,
-) -> LuaResult<&'a RuntimePath> {
+/// The directory of the optional package `name`. A package to be installed
+/// is placed in `pack/vlur/opt/` of the first directory in `&packpath`
+/// if it is not found.
+fn package_path(cache: &Cache, name: &str, install: bool) -> Option<PathBuf> {
+    let path = match cache.inner.package.opt.get(name) {
+        Some(path) => PathBuf::from(path),
+        None if install => {
+            let packpath = cache.inner.package.packpath.as_str();
+            let dir = packpath.split(nvim::OPT_SEP).find(|dir| !dir.is_empty())?;
+            Path::new(dir)
+                .join("pack")
+                .join("vlur")
+                .join("opt")
+                .join(name)
+        }
+        None => return None,
+    };
+    // Make it absolute to compare it with the files sourced by `:packadd`.
+    match env::current_dir() {
+        Ok(cwd) => Some(cwd.join(path)),
+        Err(_) => Some(path),
+    }
+}
+
+/// Index the start and optional packages in `&packpath`.
+fn read_packpath(lua: &Lua, cache: &mut Cache) -> LuaResult<()> {
     let packpath: String = nvim::get_opt(lua, "packpath")?;
 
     if !cache.is_valid || cache.inner.package.packpath != packpath {
         let mut rtp = RuntimePath::default();
+        let mut opt = HashMap::new();
         for dir in packpath.as_str().split(nvim::OPT_SEP) {
             rtp.push_package(dir);
+            for (name, path) in get_opt_packages(Path::new(dir)) {
+                // The first one in `&packpath` is used like `:packadd`.
+                opt.entry(name).or_insert(path);
+            }
         }
         cache.is_valid = false;
         cache.inner.package.packpath = packpath;
         cache.inner.package.runtimepath = rtp;
+        cache.inner.package.opt = opt;
     }
 
-    Ok(&cache.inner.package.runtimepath)
+    Ok(())
 }
@@ -0,0 +1,133 @@
+//! Runtime files placed in `{cache_dir}/runtime/` on behalf of lazy plugins,
+//! so that Neovim can find them before the plugins are loaded.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::plugin::get_health_files;
+
+/// `{dir}/colors/{name}.vim`
+///
+/// These are only used to complete `:colorscheme`, because the lazy handler
+/// adds the plugin to `&runtimepath` before Neovim searches for the file.
+pub fn write_colors(dir: &Path, colors: &[String]) -> io::Result<()> {
+    let dir = dir.join("colors");
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    if colors.is_empty() {
+        return Ok(());
+    }
+    fs::create_dir_all(&dir)?;
+
+    for name in colors {
+        // A single-quoted string in Vim script escapes `'` by doubling it.
+        let quoted = name.replace('\'', "''");
+        let script = format!(
+            "echoerr 'vlur: the plugin providing the colorscheme \"{quoted}\" is not loaded'\n"
+        );
+        fs::write(dir.join(format!("{name}.vim")), script)?;
+    }
+
+    Ok(())
+}
+
+/// `{dir}/doc/tags`
+///
+/// The help tags of the lazy plugins, which refer to the files in their
+/// `doc/` directories by absolute paths, so that `:help` works without
+/// adding the plugins to `&runtimepath`.
+pub fn write_help_tags(dir: &Path, plugins: &[(String, PathBuf)]) -> io::Result<()> {
+    let dir = dir.join("doc");
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+
+    let mut tags = Vec::new();
+    for (_, plugin_dir) in plugins {
+        let Ok(doc_dir) = plugin_dir.join("doc").canonicalize() else {
+            continue;
+        };
+        let Ok(content) = fs::read_to_string(doc_dir.join("tags")) else {
+            continue;
+        };
+        for line in content.lines() {
+            if line.starts_with("!_TAG_") {
+                continue;
+            }
+            let mut fields = line.splitn(3, '\t');
+            let (Some(tag), Some(file), Some(cmd)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let file = doc_dir.join(file);
+            tags.push(format!("{tag}\t{}\t{cmd}", file.display()));
+        }
+    }
+    if tags.is_empty() {
+        return Ok(());
+    }
+    // Vim searches the tags by binary search.
+    tags.sort();
+
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join("tags"), tags.join("\n") + "\n")?;
+
+    Ok(())
+}
+
+/// `{dir}/lua/**/health.lua` and `{dir}/lua/**/health/init.lua`
+///
+/// `:checkhealth` finds these instead of the ones in the lazy plugins.
+/// They load the plugin and then return the real module.
+pub fn write_health(dir: &Path, plugins: &[(String, PathBuf)]) -> io::Result<()> {
+    let lua_dir = dir.join("lua");
+    if lua_dir.exists() {
+        fs::remove_dir_all(&lua_dir)?;
+    }
+
+    for (id, plugin_dir) in plugins {
+        for rel_path in get_health_files(plugin_dir) {
+            let Ok(path) = plugin_dir.join(&rel_path).canonicalize() else {
+                continue;
+            };
+            let stub_path = dir.join(&rel_path);
+            if let Some(parent) = stub_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let script = format!(
+                "require('vlur').load({})\nreturn dofile({})\n",
+                lua_string(id),
+                lua_string(&path.display().to_string())
+            );
+            fs::write(stub_path, script)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Quote `s` as a Lua string literal.
+fn lua_string(s: &str) -> String {
+    let mut r = String::with_capacity(s.len() + 2);
+    r.push('"');
+    for c in s.chars() {
+        match c {
+            '"' | '\\' => {
+                r.push('\\');
+                r.push(c);
+            }
+            '\n' => r.push_str("\\n"),
+            '\r' => r.push_str("\\r"),
+            // `\ddd` takes up to three digits, so pad it not to swallow the
+            // following digits.
+            c if c.is_ascii_control() => r.push_str(&format!("\\{:03}", c as u32)),
+            c => r.push(c),
+        }
+    }
+    r.push('"');
+    r
+}
@@ -2,8 +2,18 @@ use mlua::prelude::*;
 
 use crate::utils::expand_value;
 
+#[derive(Default)]
 pub struct Progress {
     pub is_finished: bool,
+    /// From `0.0` to `1.0`, if the installer knows it.
+    pub ratio: Option<f64>,
+    /// What the installer is doing now.
+    pub message: Option<String>,
+    /// Why installing failed, e.g. the message from git.
+    pub error: Option<String>,
+    /// Revisions before and after updating, e.g. commit hashes.
+    pub old_revision: Option<String>,
+    pub new_revision: Option<String>,
 }
 
 impl<'lua> IntoLua<'lua> for Progress {
@@ -11,6 +21,11 @@ impl<'lua> IntoLua<'lua> for Progress {
         let t = lua.create_table()?;
 
         t.set("is_finished", self.is_finished)?;
+        t.set("ratio", self.ratio)?;
+        t.set("message", self.message)?;
+        t.set("error", self.error)?;
+        t.set("old_revision", self.old_revision)?;
+        t.set("new_revision", self.new_revision)?;
 
         Ok(LuaValue::Table(t))
     }
@@ -21,7 +36,19 @@ impl<'lua> FromLua<'lua> for Progress {
         let t = LuaTable::from_lua(value, lua)?;
         expand_value!(t, {
             is_finished: bool,
+            ratio: Option<f64>,
+            message: Option<String>,
+            error: Option<String>,
+            old_revision: Option<String>,
+            new_revision: Option<String>,
         });
-        Ok(Self { is_finished })
+        Ok(Self {
+            is_finished,
+            ratio,
+            message,
+            error,
+            old_revision,
+            new_revision,
+        })
     }
 }
@@ -1,3 +1,5 @@
+use mlua::prelude::*;
+
 macro_rules! expand_value {
     ($gettable:expr, { $($name:ident : $ty:ty),+ $(,)? }) => (
         $(
@@ -7,6 +9,16 @@ macro_rules! expand_value {
 }
 pub(crate) use expand_value;
 
+/// The message of the error which caused `e`, without the tracebacks of
+/// the callbacks it went through.
+pub fn error_message(e: &LuaError) -> String {
+    match e {
+        LuaError::CallbackError { cause, .. } => error_message(cause),
+        LuaError::RuntimeError(msg) => msg.clone(),
+        e => e.to_string(),
+    }
+}
+
 #[cfg(not(debug_assertions))]
 pub fn setup_logger() -> anyhow::Result<()> {
     Ok(())
@@ -12,6 +12,16 @@ pub fn test_cache(vimrc: &str) {
     run(vimrc, false, &[Headless, SetRtp, QuitWithCode]);
 }
 
+/// Unlike [`test()`], the `vimrc` must quit Neovim by itself,
+/// e.g. `source ../scripts/quit.vim` after the tested event is fired.
+pub fn test_async(vimrc: &str) {
+    run(vimrc, true, &[Headless, SetRtp]);
+}
+
+pub fn test_async_cache(vimrc: &str) {
+    run(vimrc, false, &[Headless, SetRtp]);
+}
+
 pub fn bench(vimrc: &str) {
     let count = 70;
     let warmup = 30;
@@ -0,0 +1,25 @@
+--- Utilities shared by the tests, loaded by `dofile 'tests/helper.lua'`.
+local M = {}
+
+--- Run git in {dir}, and return its output.
+---@param dir string
+---@param args string[]
+---@return string
+function M.git(dir, args)
+    local cmd = { 'git', '-C', dir, '-c', 'user.name=vlur', '-c', 'user.email=vlur@a' }
+    local output = vim.fn.system(vim.list_extend(cmd, args))
+    assert(vim.v.shell_error == 0, output)
+    return vim.trim(output)
+end
+
+--- Replace `vim.notify` to collect the messages into the returned table.
+---@return string[]
+function M.capture_notify()
+    local messages = {}
+    vim.notify = function(msg)
+        table.insert(messages, msg)
+    end
+    return messages
+end
+
+return M
@@ -0,0 +1,57 @@
+local vlur = require 'vlur'
+
+local git = dofile('tests/helper.lua').git
+
+local data_dir = vim.fn.stdpath 'data'
+local origin = data_dir .. '/error_origin'
+
+if vim.fn.isdirectory(origin) == 0 then
+    vim.fn.mkdir(origin, 'p')
+    git(origin, { 'init', '--quiet' })
+    git(origin, { 'commit', '--quiet', '--allow-empty', '--message', 'first' })
+end
+
+local messages = {}
+vim.notify = function(msg, level)
+    assert(level == vim.log.levels.ERROR)
+    table.insert(messages, msg)
+end
+
+local custom = {}
+function custom:setup(path)
+    return vim.fn.isdirectory(path) == 1
+end
+function custom:install() end
+function custom:progress()
+    return { is_finished = true, error = 'custom failure' }
+end
+
+vlur.setup {
+    missing = {
+        path = data_dir .. '/missing',
+        install = vlur.install.git(data_dir .. '/no_such_repository'),
+    },
+    custom = {
+        path = data_dir .. '/custom',
+        install = custom,
+    },
+    -- cloned, but failed to check out
+    bad_commit = {
+        path = data_dir .. '/bad_commit',
+        install = vlur.install.git(origin, { commit = 'deadbeef' }),
+    },
+}
+
+assert(#messages == 1, vim.inspect(messages))
+local lines = vim.split(messages[1], '\n')
+table.sort(lines)
+assert(#lines == 3, messages[1])
+assert(vim.startswith(lines[1], 'vlur: failed to install bad_commit: '), lines[1])
+assert(lines[1]:find('deadbeef', 1, true), lines[1])
+assert(lines[2] == 'vlur: failed to install custom: custom failure', lines[2])
+assert(vim.startswith(lines[3], 'vlur: failed to install missing: '), lines[3])
+assert(lines[3]:find('no_such_repository', 1, true), lines[3])
+assert(lines[3]:find('exit status: 128', 1, true), lines[3])
+assert(vim.fn.isdirectory(data_dir .. '/missing') == 0)
+-- removed to be installed again from the next time
+assert(vim.fn.isdirectory(data_dir .. '/bad_commit') == 0)
@@ -0,0 +1,54 @@
+local vlur = require 'vlur'
+
+local helper = dofile 'tests/helper.lua'
+local git = helper.git
+
+local data_dir = vim.fn.stdpath 'data'
+local origin = data_dir .. '/origin'
+
+local first_run = vim.fn.isdirectory(origin) == 0
+if first_run then
+    vim.fn.mkdir(origin, 'p')
+    git(origin, { 'init', '--quiet', '--initial-branch', 'main' })
+    git(origin, { 'commit', '--quiet', '--allow-empty', '--message', 'first' })
+    git(origin, { 'tag', '--annotate', '--message', 'v1', 'v1' })
+    git(origin, { 'branch', 'dev' })
+    git(origin, { 'commit', '--quiet', '--allow-empty', '--message', 'second' })
+else
+    -- Move the checkouts away from the pinned revisions.
+    git(data_dir .. '/pin_branch', { 'checkout', '--quiet', 'main' })
+    git(data_dir .. '/pin_tag', { 'checkout', '--quiet', 'main' })
+end
+
+local first = git(origin, { 'rev-parse', 'v1^{commit}' })
+local second = git(origin, { 'rev-parse', 'main' })
+
+local messages = helper.capture_notify()
+
+vlur.setup {
+    pin_branch = {
+        path = data_dir .. '/pin_branch',
+        install = vlur.install.git(origin, { branch = 'dev' }),
+    },
+    pin_tag = {
+        path = data_dir .. '/pin_tag',
+        install = vlur.install.git(origin, { tag = 'v1' }),
+    },
+    pin_commit = {
+        path = data_dir .. '/pin_commit',
+        install = vlur.install.git(origin, { commit = first:sub(1, 7) }),
+    },
+}
+
+if first_run then
+    assert(#messages == 0, vim.inspect(messages))
+    assert(git(data_dir .. '/pin_branch', { 'branch', '--show-current' }) == 'dev')
+    assert(git(data_dir .. '/pin_tag', { 'rev-parse', 'HEAD' }) == first)
+    assert(git(data_dir .. '/pin_commit', { 'rev-parse', 'HEAD' }) == first)
+else
+    assert(git(data_dir .. '/pin_tag', { 'rev-parse', 'HEAD' }) == second)
+    table.sort(messages)
+    assert(#messages == 2, vim.inspect(messages))
+    assert(messages[1]:find('pin_branch is not checked out at the branch dev', 1, true))
+    assert(messages[2]:find('pin_tag is not checked out at the tag v1', 1, true))
+end
@@ -0,0 +1,31 @@
+local vlur = require 'vlur'
+
+local git = dofile('tests/helper.lua').git
+
+local data_dir = vim.fn.stdpath 'data'
+local origin = data_dir .. '/progress_origin'
+local path = data_dir .. '/progress'
+
+vim.fn.delete(data_dir, 'rf')
+vim.fn.mkdir(origin, 'p')
+git(origin, { 'init', '--quiet' })
+vim.fn.writefile({ 'progress' }, origin .. '/README')
+git(origin, { 'add', 'README' })
+git(origin, { 'commit', '--quiet', '--message', 'first' })
+
+-- Unlike a local path, git transfers the objects and reports the progress.
+local installer = vlur.install.git('file://' .. origin)
+assert(installer:setup(path) == false)
+installer:install()
+
+local progress
+assert(vim.wait(10000, function()
+    progress = installer:progress()
+    return progress.is_finished
+end, 10))
+
+assert(progress.error == nil, progress.error)
+assert(progress.ratio == 1, vim.inspect(progress))
+assert(type(progress.message) == 'string', vim.inspect(progress))
+assert(progress.message:find '100%%', progress.message)
+assert(vim.fn.filereadable(path .. '/README') == 1)
@@ -0,0 +1,41 @@
+local vlur = require 'vlur'
+
+local helper = dofile 'tests/helper.lua'
+local git = helper.git
+
+local data_dir = vim.fn.stdpath 'data'
+local work = data_dir .. '/work'
+local origin = data_dir .. '/origin.git'
+
+if vim.fn.isdirectory(origin) == 0 then
+    vim.fn.mkdir(work, 'p')
+    git(work, { 'init', '--quiet' })
+    for _, tag in ipairs { 'v1.9.0', 'v2.0.5', 'v2.1.0', 'v2.3.1', 'v3.0.0-rc.1' } do
+        git(work, { 'commit', '--quiet', '--allow-empty', '--message', tag })
+        git(work, { 'tag', '--annotate', '--message', tag, tag })
+    end
+    git(work, { 'commit', '--quiet', '--allow-empty', '--message', 'unreleased' })
+    git(data_dir, { 'clone', '--quiet', '--bare', work, origin })
+end
+
+local messages = helper.capture_notify()
+
+vlur.setup {
+    semver = {
+        path = data_dir .. '/semver',
+        install = vlur.install.git(origin, { version = '^2.1' }),
+    },
+}
+
+assert(#messages == 0, vim.inspect(messages))
+
+local path = data_dir .. '/semver'
+local commit = git(origin, { 'rev-parse', 'v2.3.1^{commit}' })
+assert(git(path, { 'rev-parse', 'HEAD' }) == commit)
+assert(git(path, { 'config', 'vlur.version' }) == '^2.1')
+assert(git(path, { 'config', 'vlur.tag' }) == 'v2.3.1')
+assert(git(path, { 'config', 'vlur.commit' }) == commit)
+
+local ok = pcall(vlur.install.git, origin, { version = 'foo' })
+assert(not ok)
+assert(not pcall(vlur.install.git, origin, { version = '^2.1', branch = 'main' }))
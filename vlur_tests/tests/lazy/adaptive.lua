@@ -0,0 +1,47 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+local state_file = vim.fn.stdpath 'state' .. '/vlur/load_time'
+
+-- Pretend that `adaptive_slow` took 1 second to source at the last startup.
+vim.fn.mkdir(vim.fn.fnamemodify(state_file, ':h'), 'p')
+vim.fn.writefile({ '1000000\t' .. root .. '/adaptive_slow' }, state_file)
+
+vlur.setup({
+    adaptive_fast = {
+        path = root .. '/adaptive_fast',
+        lazy = 'adaptive',
+    },
+    adaptive_slow = {
+        path = root .. '/adaptive_slow',
+        lazy = 'adaptive',
+    },
+}, { adaptive_budget = 100 })
+
+assert(g.loaded_adaptive_fast == true)
+assert(g.loaded_adaptive_slow == nil)
+assert(vim.deep_equal(vlur.deferred(), { adaptive_slow = 1000 }))
+
+vim.api.nvim_create_autocmd('User', {
+    pattern = 'VeryLazy',
+    callback = function()
+        assert(g.loaded_adaptive_slow == true)
+
+        -- Both are measured again.
+        local times = {}
+        for _, line in ipairs(vim.fn.readfile(state_file)) do
+            local time, path = line:match '^(%d+)\t(.*)$'
+            times[vim.fn.fnamemodify(path, ':t')] = tonumber(time)
+        end
+        assert(times.adaptive_fast ~= nil)
+        assert(times.adaptive_slow < 1000000)
+
+        vim.cmd.source '../scripts/quit.vim'
+    end,
+})
+
+vim.defer_fn(function()
+    vim.cmd 'cquit! 1'
+end, 10000)
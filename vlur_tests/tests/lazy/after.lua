@@ -0,0 +1,34 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    after_host = {
+        path = root .. '/after_host',
+        lazy = { vlur.lazy.event('User', 'AfterHost') },
+    },
+    after_ext = {
+        path = root .. '/after_ext',
+        lazy = { vlur.lazy.after 'after_host' },
+    },
+    after_ext_ext = {
+        path = root .. '/after_ext_ext',
+        lazy = { vlur.lazy.after 'after_ext' },
+    },
+    after_start = {
+        path = root .. '/after_start',
+    },
+    after_start_ext = {
+        path = root .. '/after_start_ext',
+        lazy = { vlur.lazy.after 'after_start' },
+    },
+}
+
+-- the host is not lazy, so the extension is loaded after sourcing its files
+assert(vim.deep_equal(g.after_order, { 'after_start', 'after_start_ext' }))
+
+g.after_order = {}
+vim.api.nvim_exec_autocmds('User', { pattern = 'AfterHost' })
+assert(vim.deep_equal(g.after_order, { 'after_host', 'after_ext', 'after_ext_ext' }))
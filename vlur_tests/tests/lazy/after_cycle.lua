@@ -0,0 +1,28 @@
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+local messages = dofile('tests/helper.lua').capture_notify()
+
+vlur.setup {
+    after_cycle_a = {
+        path = root .. '/after_cycle_a',
+        lazy = { vlur.lazy.after 'after_cycle_b' },
+    },
+    after_cycle_b = {
+        path = root .. '/after_cycle_b',
+        lazy = { vlur.lazy.after 'after_cycle_a' },
+    },
+    after_unknown = {
+        path = root .. '/after_ext',
+        lazy = { vlur.lazy.after 'unknown' },
+    },
+}
+
+-- the setup goes on without the plugins
+assert(#messages == 2, vim.inspect(messages))
+local text = table.concat(messages, '\n')
+assert(text:find 'circular dependency', text)
+assert(text:find 'plugin `unknown` does not exist', text)
+assert(not text:find 'stack traceback', text)
+assert(vim.g.after_order == nil)
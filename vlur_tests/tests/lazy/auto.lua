@@ -0,0 +1,47 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+local messages = dofile('tests/helper.lua').capture_notify()
+
+vlur.setup {
+    auto_cmd = {
+        path = root .. '/auto_cmd',
+        lazy = 'auto',
+    },
+    auto_key = {
+        path = root .. '/auto_key',
+        lazy = 'auto',
+    },
+    auto_func = {
+        path = root .. '/auto_func',
+        lazy = 'auto',
+    },
+    auto_none = {
+        path = root .. '/auto_none',
+        lazy = 'auto',
+    },
+}
+
+-- nothing would load it, so it is not lazy
+assert(g.loaded_auto_none == 1)
+assert(#messages == 1, vim.inspect(messages))
+assert(messages[1]:find 'no triggers', messages[1])
+
+assert(g.loaded_auto_cmd == nil)
+assert(g.loaded_auto_key == nil)
+assert(g.loaded_auto_func == nil)
+
+-- `-nargs=?` takes all of them as one argument
+vim.cmd [[AutoCmd foo  bar\ baz]]
+assert(g.auto_cmd == [[foo  bar\ baz]], g.auto_cmd)
+
+vim.keymap.set('n', '<Space>a', '<Plug>(auto-key)')
+local keys = vim.api.nvim_replace_termcodes('<Space>a', true, true, true)
+vim.api.nvim_feedkeys(keys, 'x', false)
+assert(g.auto_key == 1)
+
+assert(vim.fn['auto_func#value']() == 42)
+assert(g.loaded_auto_func == 1)
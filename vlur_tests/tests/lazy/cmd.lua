@@ -0,0 +1,33 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    cmd = {
+        path = root .. '/cmd',
+        lazy = { vlur.lazy.cmd 'LazyCmd' },
+    },
+    cmd_complete = {
+        path = root .. '/cmd_complete',
+        lazy = { vlur.lazy.cmd { 'LazyCmdComplete' } },
+    },
+}
+
+assert(g.loaded_cmd == nil)
+assert(g.loaded_cmd_complete == nil)
+
+vim.api.nvim_buf_set_lines(0, 0, -1, false, { 'a', 'b', 'c' })
+vim.cmd '2,3LazyCmd! foo bar'
+assert(g.loaded_cmd ~= nil)
+assert(vim.deep_equal(g.lazy_cmd, {
+    bang = true,
+    args = { 'foo', 'bar' },
+    line1 = 2,
+    line2 = 3,
+}))
+
+local completion = vim.fn.getcompletion('LazyCmdComplete ', 'cmdline')
+assert(g.loaded_cmd_complete ~= nil)
+assert(vim.deep_equal(completion, { 'foo', 'bar' }))
@@ -0,0 +1,24 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    colorscheme = {
+        path = root .. '/colorscheme',
+        lazy = { vlur.lazy.colorscheme() },
+    },
+}
+
+assert(vim.tbl_contains(vim.fn.getcompletion('lazy', 'color'), 'lazycolor'))
+
+vim.cmd.colorscheme 'lazycolor'
+assert(g.colors_name == 'lazycolor')
+
+-- The name is quoted in the stub.
+assert(vim.tbl_contains(vim.fn.getcompletion('lazy', 'color'), "lazy'quote"))
+local stub = vim.api.nvim_get_runtime_file("colors/lazy'quote.vim", false)[1]
+local ok, err = pcall(vim.cmd.source, stub)
+assert(not ok)
+assert(err:find([[colorscheme "lazy'quote" is not loaded]], 1, true), err)
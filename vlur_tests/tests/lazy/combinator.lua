@@ -0,0 +1,74 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+local lazy = vlur.lazy
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    any = {
+        path = root .. '/any',
+        lazy = { lazy.any { lazy.event('User', 'A'), lazy.event('User', 'B') } },
+    },
+    all = {
+        path = root .. '/all',
+        lazy = { lazy.all { lazy.event('User', 'A'), lazy.event('User', 'B') } },
+    },
+    when = {
+        path = root .. '/when',
+        lazy = {
+            lazy.when(function()
+                return g.enable_when == true
+            end, lazy.event('User', 'A')),
+        },
+    },
+    all_cmd = {
+        path = root .. '/all_cmd',
+        lazy = { lazy.all { lazy.cmd 'AllCmd', lazy.event('User', 'C') } },
+    },
+    when_keys = {
+        path = root .. '/when_keys',
+        lazy = {
+            lazy.when(function()
+                return g.enable_when_keys == true
+            end, lazy.keys '<Space>w'),
+        },
+    },
+}
+
+local function fire(pattern)
+    vim.api.nvim_exec_autocmds('User', { pattern = pattern })
+end
+
+fire 'A'
+assert(g.loaded_any ~= nil)
+assert(g.loaded_all == nil)
+assert(g.loaded_when == nil)
+
+fire 'B'
+assert(g.loaded_all ~= nil)
+
+g.enable_when = true
+fire 'A'
+assert(g.loaded_when ~= nil)
+
+-- The stub must not be executed again before the plugin is loaded.
+vim.cmd 'AllCmd'
+assert(g.loaded_all_cmd == nil)
+assert(g.all_cmd == nil)
+
+fire 'C'
+assert(g.loaded_all_cmd ~= nil)
+assert(g.all_cmd == nil)
+vim.cmd 'AllCmd'
+assert(g.all_cmd == 1)
+
+local keys = vim.api.nvim_replace_termcodes('<Space>w', true, true, true)
+vim.api.nvim_feedkeys(keys, 'x', false)
+assert(g.loaded_when_keys == nil)
+assert(g.when_keys == nil)
+
+g.enable_when_keys = true
+vim.api.nvim_feedkeys(keys, 'x', false)
+assert(g.loaded_when_keys ~= nil)
+assert(g.when_keys == 1)
@@ -0,0 +1,25 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    event_read = {
+        path = root .. '/event_read',
+        lazy = { vlur.lazy.event('FileType', 'lua') },
+    },
+    event_new = {
+        path = root .. '/event_new',
+        lazy = { vlur.lazy.event('FileType', 'lazyevent') },
+    },
+}
+
+vim.cmd 'filetype on'
+
+vim.cmd.edit(root .. '/event_read/plugin/event_read.lua')
+assert(vim.deep_equal(g.event_read, { 'BufReadPre', 'BufReadPost', 'FileType' }))
+
+vim.cmd.edit 'not_exists.lazyevent'
+vim.bo.filetype = 'lazyevent'
+assert(vim.deep_equal(g.event_new, { 'BufNewFile', 'FileType' }))
@@ -0,0 +1,30 @@
+local g = vim.g
+local api = vim.api
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    ft = {
+        path = root .. '/ft',
+        lazy = { vlur.lazy.ft 'lazyft' },
+    },
+}
+
+vim.cmd 'filetype plugin indent on'
+
+assert(g.loaded_ft == nil)
+
+-- A buffer whose filetype was set before loading the plugin.
+local other = api.nvim_create_buf(true, false)
+api.nvim_buf_call(other, function()
+    vim.cmd 'noautocmd setlocal filetype=lazyft'
+end)
+
+vim.bo.filetype = 'lazyft'
+
+assert(g.loaded_ft ~= nil)
+-- `ftplugin/` is sourced once for each buffer.
+assert(vim.b.lazy_ftplugin == 1)
+assert(vim.b[other].lazy_ftplugin == 1)
@@ -0,0 +1,21 @@
+local fn = vim.fn
+
+local vlur = require 'vlur'
+
+local root = fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    func = {
+        path = root .. '/func',
+        lazy = { vlur.lazy.func() },
+    },
+    func_named = {
+        path = root .. '/func_named',
+        lazy = { vlur.lazy.func 'LazyFuncNamed' },
+    },
+}
+
+assert(fn.exists '*LazyFuncNamed' == 0)
+assert(fn.LazyFuncNamed() == 'named')
+
+assert(fn['lazyfunc#util#add'](1, 2) == 3)
@@ -0,0 +1,26 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    health_lazy = {
+        path = root .. '/health_lazy',
+        lazy = { vlur.lazy.cmd 'HealthLazy' },
+    },
+    ['health "quote\\'] = {
+        path = root .. '/health_quote',
+        lazy = { vlur.lazy.cmd 'HealthQuote' },
+    },
+}
+
+assert(vim.tbl_contains(vim.fn.getcompletion('checkhealth health_', 'cmdline'), 'health_lazy'))
+assert(g.loaded_health_lazy == nil)
+
+vim.cmd.checkhealth 'health_lazy'
+assert(g.health_lazy_checked == true)
+
+-- The plugin ID is quoted in the stub.
+vim.cmd.checkhealth 'health_quote'
+assert(g.health_quote_checked == true)
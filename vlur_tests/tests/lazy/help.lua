@@ -0,0 +1,15 @@
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    help = {
+        path = root .. '/help',
+        lazy = { vlur.lazy.cmd 'LazyHelp' },
+    },
+}
+
+vim.cmd.help 'help_lazy-tag'
+assert(vim.bo.buftype == 'help')
+assert(vim.fn.expand '%:t' == 'help_lazy.txt')
+assert(vim.g.loaded_help == nil)
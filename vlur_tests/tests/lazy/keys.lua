@@ -0,0 +1,73 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    keys = {
+        path = root .. '/keys',
+        lazy = {
+            vlur.lazy.keys {
+                '<Space>k',
+                { '<Space>x', mode = { 'n', 'x' }, desc = 'stub' },
+            },
+        },
+    },
+    keys_count = {
+        path = root .. '/keys_count',
+        lazy = { vlur.lazy.keys '<Space>c' },
+    },
+    keys_visual = {
+        path = root .. '/keys_visual',
+        lazy = { vlur.lazy.keys { { '<Space>v', mode = 'x' } } },
+    },
+    keys_operator = {
+        path = root .. '/keys_operator',
+        lazy = { vlur.lazy.keys { { '<Space>o', mode = 'o' } } },
+    },
+}
+
+local function has_map(lhs, mode)
+    return not vim.tbl_isempty(vim.fn.maparg(lhs, mode, false, true))
+end
+
+assert(g.loaded_keys == nil)
+assert(has_map('<Space>k', 'n'))
+assert(has_map('<Space>x', 'n'))
+assert(has_map('<Space>x', 'x'))
+
+local function feed(keys)
+    keys = vim.api.nvim_replace_termcodes(keys, true, true, true)
+    vim.api.nvim_feedkeys(keys, 'x', false)
+end
+
+feed '<Space>k'
+
+assert(g.loaded_keys ~= nil)
+assert(g.lazy_keys == 1)
+assert(not has_map('<Space>x', 'n'))
+assert(not has_map('<Space>x', 'x'))
+
+-- the count and the register are replayed
+feed '"a3<Space>c'
+assert(g.loaded_keys_count ~= nil)
+assert(vim.deep_equal(g.keys_count, { count = 3, register = 'a' }))
+
+vim.api.nvim_buf_set_lines(0, 0, -1, false, { 'foo bar baz' })
+vim.api.nvim_win_set_cursor(0, { 1, 0 })
+feed 'v2<Space>v<Esc>'
+assert(g.loaded_keys_visual ~= nil)
+assert(vim.deep_equal(g.keys_visual, { mode = 'v', count = 2 }))
+
+-- the pending operator is applied to the replayed keys, and the plugin
+-- is loaded outside the expression
+vim.api.nvim_win_set_cursor(0, { 1, 4 })
+feed '"ad<Space>o'
+assert(g.loaded_keys_operator ~= nil)
+assert(vim.api.nvim_get_current_line() == 'foo  baz')
+assert(vim.fn.getreg 'a' == 'bar')
+
+local ok, err = pcall(vlur.lazy.keys, { { '<Space>b', buffer = true } })
+assert(not ok)
+assert(err:find('`buffer = true` is not supported', 1, true), err)
@@ -0,0 +1,57 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    load_a = {
+        path = root .. '/load_a',
+        lazy = { vlur.lazy.event('User', 'LoadA') },
+    },
+    load_b = {
+        path = root .. '/load_b',
+        lazy = { vlur.lazy.event('User', 'LoadB') },
+    },
+    load_fail = {
+        path = root .. '/load_fail',
+        lazy = { vlur.lazy.event('User', 'LoadFail') },
+    },
+}
+
+assert(g.load_a_count == nil)
+assert(g.load_b_count == nil)
+
+vlur.load 'load_a'
+assert(g.load_a_count == 1)
+
+-- never loaded twice
+vlur.load 'load_a'
+vim.api.nvim_exec_autocmds('User', { pattern = 'LoadA' })
+assert(g.load_a_count == 1)
+
+local ok, err = pcall(vlur.load, 'unknown')
+assert(not ok)
+assert(err:find 'plugin `unknown` does not exist', err)
+
+-- A plugin which failed to load can be loaded again.
+vim.cmd 'filetype on'
+vim.cmd.edit 'not_exists.loadfail'
+vim.api.nvim_create_autocmd('FileType', {
+    pattern = 'loadfail',
+    callback = function()
+        if g.load_fail then
+            error 'load_fail'
+        end
+    end,
+})
+g.load_fail = true
+assert(not pcall(vlur.load, 'load_fail'))
+assert(g.load_fail_count == 1)
+g.load_fail = false
+vlur.load 'load_fail'
+assert(g.load_fail_count == 2)
+
+vlur.load_all()
+assert(g.load_a_count == 1)
+assert(g.load_b_count == 1)
@@ -0,0 +1,28 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    loader_order = {
+        path = root .. '/loader_order',
+        lazy = { vlur.lazy.ft 'lazyorder' },
+    },
+}
+
+-- `ftdetect/` is sourced at startup.
+assert(vim.deep_equal(g.loader_order, { 'ftdetect' }))
+
+vim.cmd 'filetype on'
+
+vim.cmd.edit 'not_exists.lazyorder2'
+assert(vim.bo.filetype == '')
+
+vim.cmd.edit 'not_exists.lazyorder'
+assert(vim.bo.filetype == 'lazyorder')
+assert(vim.deep_equal(g.loader_order, { 'ftdetect', 'plugin', 'after/plugin' }))
+
+-- detected again after loading
+vim.cmd.buffer 'not_exists.lazyorder2'
+assert(vim.bo.filetype == 'lazyorder2')
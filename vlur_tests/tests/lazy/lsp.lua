@@ -0,0 +1,92 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    lsp_name = {
+        path = root .. '/lsp_name',
+        lazy = { vlur.lazy.lsp { name = 'lazy_ls_a' } },
+    },
+    lsp_cap = {
+        path = root .. '/lsp_cap',
+        lazy = { vlur.lazy.lsp { capability = 'inlayHintProvider' } },
+    },
+    lsp_never = {
+        path = root .. '/lsp_never',
+        lazy = {
+            vlur.lazy.lsp {
+                name = 'lazy_ls_a',
+                capability = 'workspace.workspaceFolders',
+            },
+        },
+    },
+    lsp_buf = {
+        path = root .. '/lsp_buf',
+        lazy = { vlur.lazy.lsp { name = 'lazy_ls_c' } },
+    },
+}
+
+--- A fake language server running in this process.
+local function server(capabilities)
+    return function(dispatchers)
+        local closing = false
+        return {
+            request = function(method, _, callback)
+                if method == 'initialize' then
+                    callback(nil, { capabilities = capabilities })
+                else
+                    callback(nil, nil)
+                end
+                return true, 1
+            end,
+            notify = function(method)
+                if method == 'exit' then
+                    dispatchers.on_exit(0, 15)
+                end
+                return true
+            end,
+            is_closing = function()
+                return closing
+            end,
+            terminate = function()
+                closing = true
+            end,
+        }
+    end
+end
+
+local function attach(name, capabilities, bufnr)
+    bufnr = bufnr or vim.api.nvim_get_current_buf()
+    local id = vim.lsp.start({
+        name = name,
+        cmd = server(capabilities),
+        root_dir = vim.fn.getcwd(),
+    }, { bufnr = bufnr })
+    assert(vim.wait(1000, function()
+        return vim.lsp.buf_is_attached(bufnr, id)
+    end))
+end
+
+attach('lazy_ls_b', {})
+assert(g.loaded_lsp_name == nil)
+assert(g.loaded_lsp_cap == nil)
+
+attach('lazy_ls_a', { inlayHintProvider = true })
+assert(vim.wait(1000, function()
+    return g.attached_lsp_name ~= nil and g.attached_lsp_cap ~= nil
+end))
+assert(g.attached_lsp_name == 'lazy_ls_a')
+assert(g.attached_lsp_cap == 'lazy_ls_a')
+assert(g.loaded_lsp_never == nil)
+
+-- Attach to a buffer which is not the current one.
+local buf = vim.api.nvim_create_buf(true, false)
+attach('lazy_ls_c', {}, buf)
+assert(vim.wait(1000, function()
+    return g.attached_lsp_buf ~= nil
+end))
+assert(g.loaded_lsp_buf == true)
+assert(vim.deep_equal(g.attached_lsp_buf, { buf, 'lazy_ls_c' }))
+assert(vim.api.nvim_get_current_buf() ~= buf)
@@ -0,0 +1,27 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    module = {
+        path = root .. '/module',
+        lazy = { vlur.lazy.module() },
+    },
+    module_named = {
+        path = root .. '/module_named',
+        lazy = { vlur.lazy.module 'lazymod_named' },
+    },
+}
+
+assert(g.loaded_module == nil)
+assert(require('lazymod.sub').name == 'lazymod.sub')
+assert(g.loaded_module ~= nil)
+assert(require('lazymod').name == 'lazymod')
+
+assert(g.loaded_module_named == nil)
+assert(require('lazymod_named').name == 'lazymod_named')
+assert(g.loaded_module_named ~= nil)
+
+assert(not pcall(require, 'lazymod_unknown'))
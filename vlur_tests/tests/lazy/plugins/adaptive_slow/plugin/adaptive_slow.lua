@@ -0,0 +1 @@
+vim.g.loaded_adaptive_slow = true
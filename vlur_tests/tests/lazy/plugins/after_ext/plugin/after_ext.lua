@@ -0,0 +1 @@
+vim.g.after_order = vim.list_extend(vim.g.after_order or {}, { 'after_ext' })
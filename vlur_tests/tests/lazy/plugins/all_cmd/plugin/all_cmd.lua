@@ -0,0 +1,5 @@
+vim.g.loaded_all_cmd = true
+
+vim.api.nvim_create_user_command('AllCmd', function()
+    vim.g.all_cmd = (vim.g.all_cmd or 0) + 1
+end, {})
@@ -0,0 +1,6 @@
+if exists('g:loaded_auto_cmd')
+  finish
+endif
+let g:loaded_auto_cmd = 1
+
+command! -nargs=? AutoCmd let g:auto_cmd = <q-args>
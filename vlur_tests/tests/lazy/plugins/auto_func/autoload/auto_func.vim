@@ -0,0 +1,3 @@
+function! auto_func#value() abort
+  return 42
+endfunction
@@ -0,0 +1,3 @@
+let g:loaded_auto_key = 1
+
+nnoremap <silent> <Plug>(auto-key) <Cmd>let g:auto_key = 1<CR>
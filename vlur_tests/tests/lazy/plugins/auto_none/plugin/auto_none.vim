@@ -0,0 +1 @@
+let g:loaded_auto_none = 1
@@ -0,0 +1,10 @@
+vim.g.loaded_cmd = true
+
+vim.api.nvim_create_user_command('LazyCmd', function(ev)
+    vim.g.lazy_cmd = {
+        bang = ev.bang,
+        args = ev.fargs,
+        line1 = ev.line1,
+        line2 = ev.line2,
+    }
+end, { bang = true, range = true, nargs = '*' })
@@ -0,0 +1,8 @@
+vim.g.loaded_cmd_complete = true
+
+vim.api.nvim_create_user_command('LazyCmdComplete', function() end, {
+    nargs = 1,
+    complete = function()
+        return { 'foo', 'bar' }
+    end,
+})
@@ -0,0 +1,2 @@
+vim.cmd 'highlight clear'
+vim.g.colors_name = 'lazycolor'
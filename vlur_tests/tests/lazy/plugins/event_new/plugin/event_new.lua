@@ -0,0 +1,11 @@
+local events = {}
+vim.g.event_new = events
+
+for _, event in ipairs { 'BufReadPre', 'BufReadPost', 'BufNewFile', 'FileType' } do
+    vim.api.nvim_create_autocmd(event, {
+        callback = function(ev)
+            table.insert(events, ev.event)
+            vim.g.event_new = events
+        end,
+    })
+end
@@ -0,0 +1 @@
+vim.b.lazy_ftplugin = (vim.b.lazy_ftplugin or 0) + 1
@@ -0,0 +1,3 @@
+function! lazyfunc#util#add(a, b) abort
+    return a:a + a:b
+endfunction
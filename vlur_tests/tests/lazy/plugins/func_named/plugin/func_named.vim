@@ -0,0 +1,3 @@
+function! LazyFuncNamed() abort
+    return 'named'
+endfunction
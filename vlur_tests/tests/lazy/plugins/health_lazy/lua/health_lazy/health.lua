@@ -0,0 +1,6 @@
+return {
+    check = function()
+        vim.g.health_lazy_checked = vim.g.loaded_health_lazy
+        vim.health.ok 'loaded'
+    end,
+}
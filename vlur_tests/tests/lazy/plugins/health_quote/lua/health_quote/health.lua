@@ -0,0 +1,6 @@
+return {
+    check = function()
+        vim.g.health_quote_checked = vim.g.loaded_health_quote
+        vim.health.ok 'loaded'
+    end,
+}
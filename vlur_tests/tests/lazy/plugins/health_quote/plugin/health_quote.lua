@@ -0,0 +1 @@
+vim.g.loaded_health_quote = true
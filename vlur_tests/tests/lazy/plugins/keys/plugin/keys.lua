@@ -0,0 +1,5 @@
+vim.g.loaded_keys = true
+
+vim.keymap.set('n', '<Space>k', function()
+    vim.g.lazy_keys = (vim.g.lazy_keys or 0) + 1
+end)
@@ -0,0 +1,5 @@
+vim.g.loaded_keys_count = true
+
+vim.keymap.set('n', '<Space>c', function()
+    vim.g.keys_count = { count = vim.v.count, register = vim.v.register }
+end)
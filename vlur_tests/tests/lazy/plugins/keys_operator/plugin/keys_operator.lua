@@ -0,0 +1,6 @@
+-- not allowed under textlock
+vim.cmd 'split | close'
+
+vim.g.loaded_keys_operator = true
+
+vim.keymap.set('o', '<Space>o', 'iw')
@@ -0,0 +1,5 @@
+vim.g.loaded_keys_visual = true
+
+vim.keymap.set('x', '<Space>v', function()
+    vim.g.keys_visual = { mode = vim.api.nvim_get_mode().mode, count = vim.v.count }
+end)
@@ -0,0 +1 @@
+vim.g.load_a_count = (vim.g.load_a_count or 0) + 1
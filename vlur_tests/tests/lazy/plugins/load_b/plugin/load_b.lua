@@ -0,0 +1 @@
+vim.g.load_b_count = (vim.g.load_b_count or 0) + 1
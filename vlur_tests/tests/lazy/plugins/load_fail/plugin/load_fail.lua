@@ -0,0 +1,3 @@
+vim.g.load_fail_count = (vim.g.load_fail_count or 0) + 1
+
+vim.filetype.add { extension = { loadfail = 'loadfail' } }
@@ -0,0 +1 @@
+vim.g.loader_order = vim.list_extend(vim.g.loader_order or {}, { 'after/plugin' })
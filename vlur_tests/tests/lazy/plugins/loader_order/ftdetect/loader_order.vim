@@ -0,0 +1,3 @@
+let g:loader_order = add(get(g:, 'loader_order', []), 'ftdetect')
+
+autocmd BufNewFile,BufRead *.lazyorder setfiletype lazyorder
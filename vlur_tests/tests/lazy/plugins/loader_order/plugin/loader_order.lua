@@ -0,0 +1,3 @@
+vim.g.loader_order = vim.list_extend(vim.g.loader_order or {}, { 'plugin' })
+
+vim.filetype.add { extension = { lazyorder2 = 'lazyorder2' } }
@@ -0,0 +1,8 @@
+vim.g.loaded_lsp_buf = true
+
+vim.api.nvim_create_autocmd('LspAttach', {
+    callback = function(ev)
+        local name = vim.lsp.get_client_by_id(ev.data.client_id).name
+        vim.g.attached_lsp_buf = { ev.buf, name }
+    end,
+})
@@ -0,0 +1,7 @@
+vim.g.loaded_lsp_never = true
+
+vim.api.nvim_create_autocmd('LspAttach', {
+    callback = function(ev)
+        vim.g.attached_lsp_never = vim.lsp.get_client_by_id(ev.data.client_id).name
+    end,
+})
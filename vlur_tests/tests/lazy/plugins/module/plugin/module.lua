@@ -0,0 +1 @@
+vim.g.loaded_module = true
@@ -0,0 +1,3 @@
+vim.g.loaded_module_named = true
+
+return { name = 'lazymod_named' }
@@ -0,0 +1 @@
+vim.g.loaded_root_cwd = true
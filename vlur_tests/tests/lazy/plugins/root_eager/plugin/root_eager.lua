@@ -0,0 +1 @@
+vim.g.root_order = vim.list_extend(vim.g.root_order or {}, { 'root_eager' })
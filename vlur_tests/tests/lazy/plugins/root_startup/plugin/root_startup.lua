@@ -0,0 +1,2 @@
+vim.g.loaded_root_startup = true
+vim.g.root_order = vim.list_extend(vim.g.root_order or {}, { 'root_startup' })
@@ -0,0 +1 @@
+vim.g.loaded_very_lazy = true
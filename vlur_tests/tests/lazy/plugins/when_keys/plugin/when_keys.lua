@@ -0,0 +1,5 @@
+vim.g.loaded_when_keys = true
+
+vim.keymap.set('n', '<Space>w', function()
+    vim.g.when_keys = (vim.g.when_keys or 0) + 1
+end)
@@ -0,0 +1,58 @@
+local g = vim.g
+local fn = vim.fn
+
+local vlur = require 'vlur'
+
+local root = fn.getcwd() .. '/tests/lazy/plugins'
+
+local project = fn.tempname()
+fn.mkdir(project .. '/sub', 'p')
+fn.writefile({}, project .. '/vlur_root_marker')
+fn.writefile({}, project .. '/project.lazyroot')
+
+local other = fn.tempname()
+fn.mkdir(other, 'p')
+fn.writefile({}, other .. '/vlur_root_buf_marker')
+
+vlur.setup {
+    root_startup = {
+        path = root .. '/root_startup',
+        -- the working directory of the tests
+        lazy = { vlur.lazy.root 'Cargo.toml' },
+    },
+    root_cwd = {
+        path = root .. '/root_cwd',
+        lazy = { vlur.lazy.root { 'not_exists', 'vlur_root_marker' } },
+    },
+    root_glob = {
+        path = root .. '/root_glob',
+        lazy = { vlur.lazy.root '*.lazyroot' },
+    },
+    root_buf = {
+        path = root .. '/root_buf',
+        lazy = { vlur.lazy.root 'vlur_root_buf_marker' },
+    },
+    root_never = {
+        path = root .. '/root_never',
+        lazy = { vlur.lazy.root 'not_exists' },
+    },
+    root_eager = {
+        path = root .. '/root_eager',
+    },
+}
+
+assert(g.loaded_root_startup == true)
+-- loaded after sourcing the plugins which are not lazy
+assert(vim.deep_equal(g.root_order, { 'root_eager', 'root_startup' }))
+assert(g.loaded_root_cwd == nil)
+assert(g.loaded_root_glob == nil)
+
+vim.cmd.cd(project .. '/sub')
+assert(g.loaded_root_cwd == true)
+assert(g.loaded_root_glob == true)
+assert(g.loaded_root_buf == nil)
+
+vim.cmd.edit(other .. '/file.txt')
+assert(g.loaded_root_buf == true)
+
+assert(g.loaded_root_never == nil)
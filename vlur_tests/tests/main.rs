@@ -1,4 +1,4 @@
-use vlur_tests::{test, test_cache};
+use vlur_tests::{test, test_async, test_async_cache, test_cache};
 
 #[test]
 fn setup() {
@@ -18,8 +18,160 @@ fn packpath() {
     test_cache("tests/packpath/read.lua");
 }
 
+#[test]
+fn packpath_opt() {
+    test("tests/packpath/opt.lua");
+    test_cache("tests/packpath/opt.lua");
+}
+
+#[test]
+fn packpath_packadd() {
+    test_async("tests/packpath/packadd.lua");
+    test_async_cache("tests/packpath/packadd.lua");
+}
+
 #[test]
 fn install() {
     test("tests/install.lua");
     test_cache("tests/install.lua");
 }
+
+#[test]
+fn install_pin() {
+    test("tests/install_pin.lua");
+    test_cache("tests/install_pin.lua");
+}
+
+#[test]
+fn install_version() {
+    test("tests/install_version.lua");
+    test_cache("tests/install_version.lua");
+}
+
+#[test]
+fn install_progress() {
+    test("tests/install_progress.lua");
+    test_cache("tests/install_progress.lua");
+}
+
+#[test]
+fn install_error() {
+    test("tests/install_error.lua");
+    test_cache("tests/install_error.lua");
+}
+
+#[test]
+fn update() {
+    test("tests/update.lua");
+    test_cache("tests/update.lua");
+}
+
+#[test]
+fn lazy_event() {
+    test("tests/lazy/event.lua");
+    test_cache("tests/lazy/event.lua");
+}
+
+#[test]
+fn lazy_cmd() {
+    test("tests/lazy/cmd.lua");
+    test_cache("tests/lazy/cmd.lua");
+}
+
+#[test]
+fn lazy_keys() {
+    test("tests/lazy/keys.lua");
+    test_cache("tests/lazy/keys.lua");
+}
+
+#[test]
+fn lazy_ft() {
+    test("tests/lazy/ft.lua");
+    test_cache("tests/lazy/ft.lua");
+}
+
+#[test]
+fn lazy_module() {
+    test("tests/lazy/module.lua");
+    test_cache("tests/lazy/module.lua");
+}
+
+#[test]
+fn lazy_colorscheme() {
+    test("tests/lazy/colorscheme.lua");
+    test_cache("tests/lazy/colorscheme.lua");
+}
+
+#[test]
+fn lazy_func() {
+    test("tests/lazy/func.lua");
+    test_cache("tests/lazy/func.lua");
+}
+
+#[test]
+fn very_lazy() {
+    test_async("tests/very_lazy.lua");
+    test_async_cache("tests/very_lazy.lua");
+}
+
+#[test]
+fn lazy_combinator() {
+    test("tests/lazy/combinator.lua");
+    test_cache("tests/lazy/combinator.lua");
+}
+
+#[test]
+fn lazy_after() {
+    test("tests/lazy/after.lua");
+    test_cache("tests/lazy/after.lua");
+    test("tests/lazy/after_cycle.lua");
+    test_cache("tests/lazy/after_cycle.lua");
+}
+
+#[test]
+fn lazy_root() {
+    test("tests/lazy/root.lua");
+    test_cache("tests/lazy/root.lua");
+}
+
+#[test]
+fn lazy_lsp() {
+    test("tests/lazy/lsp.lua");
+    test_cache("tests/lazy/lsp.lua");
+}
+
+#[test]
+fn load() {
+    test("tests/lazy/load.lua");
+    test_cache("tests/lazy/load.lua");
+}
+
+#[test]
+fn lazy_loader() {
+    test("tests/lazy/loader.lua");
+    test_cache("tests/lazy/loader.lua");
+}
+
+#[test]
+fn lazy_help() {
+    test("tests/lazy/help.lua");
+    test_cache("tests/lazy/help.lua");
+}
+
+#[test]
+fn lazy_health() {
+    test("tests/lazy/health.lua");
+    test_cache("tests/lazy/health.lua");
+}
+
+#[test]
+fn lazy_auto() {
+    test("tests/lazy/auto.lua");
+    test_cache("tests/lazy/auto.lua");
+}
+
+#[test]
+fn lazy_adaptive() {
+    test_async("tests/lazy/adaptive.lua");
+    test_async_cache("tests/lazy/adaptive.lua");
+}
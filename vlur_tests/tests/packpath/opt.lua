@@ -0,0 +1,31 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+vim.go.packpath = 'tests/packpath/package_root'
+
+vlur.setup {
+    baz = {
+        package = 'baz',
+        lazy = { vlur.lazy.cmd 'Baz' },
+    },
+    qux = {
+        package = 'qux',
+        lazy = { vlur.lazy.event('User', 'Qux') },
+    },
+}
+
+assert(g.loaded_baz == nil)
+assert(g.loaded_qux == nil)
+
+vim.cmd 'Baz'
+assert(g.loaded_baz == 1)
+assert(g.baz_called == true)
+
+-- loaded by `:packadd` instead of the handler
+vim.cmd.packadd 'qux'
+assert(g.loaded_qux == 1)
+
+vim.api.nvim_exec_autocmds('User', { pattern = 'Qux' })
+vlur.load 'qux'
+assert(g.loaded_qux == 1)
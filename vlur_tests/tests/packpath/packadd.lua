@@ -0,0 +1,66 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local git = dofile('tests/helper.lua').git
+
+local data_dir = vim.fn.stdpath 'data'
+local origin = data_dir .. '/packadd_origin'
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vim.fn.delete(data_dir, 'rf')
+vim.fn.mkdir(origin .. '/plugin', 'p')
+vim.fn.writefile({ 'vim.g.loaded_installed = 1' }, origin .. '/plugin/installed.lua')
+git(origin, { 'init', '--quiet' })
+git(origin, { 'add', '.' })
+git(origin, { 'commit', '--quiet', '--message', 'first' })
+
+vim.go.packpath = data_dir .. '/site,tests/packpath/package_root'
+
+vlur.setup {
+    -- no files are sourced by `:packadd`
+    quux = {
+        package = 'quux',
+        lazy = { vlur.lazy.event('User', 'Quux') },
+    },
+    after_ext = {
+        path = root .. '/after_ext',
+        lazy = { vlur.lazy.after 'quux' },
+    },
+    installed = {
+        package = 'installed',
+        install = vlur.install.git(origin),
+        lazy = { vlur.lazy.event('User', 'Installed') },
+    },
+}
+
+local installed = data_dir .. '/site/pack/vlur/opt/installed'
+assert(vim.fn.filereadable(installed .. '/plugin/installed.lua') == 1)
+assert(g.loaded_installed == nil)
+assert(g.after_order == nil)
+
+vim.api.nvim_create_autocmd('VimEnter', {
+    callback = vim.schedule_wrap(function()
+        vim.cmd.packadd 'quux'
+        assert(require('quux').name == 'quux')
+        vim.cmd.packadd 'installed'
+        assert(g.loaded_installed == 1)
+        -- `&runtimepath` is no longer watched
+        assert(vim.tbl_isempty(vim.api.nvim_get_autocmds {
+            event = 'OptionSet',
+            pattern = 'runtimepath',
+        }))
+
+        vim.schedule(function()
+            assert(vim.deep_equal(g.after_order, { 'after_ext' }))
+            vlur.load 'quux'
+            vim.api.nvim_exec_autocmds('User', { pattern = 'Quux' })
+            assert(vim.deep_equal(g.after_order, { 'after_ext' }))
+            vim.cmd.source '../scripts/quit.vim'
+        end)
+    end),
+})
+
+vim.defer_fn(function()
+    vim.cmd 'cquit! 1'
+end, 10000)
@@ -0,0 +1,5 @@
+vim.g.loaded_baz = (vim.g.loaded_baz or 0) + 1
+
+vim.api.nvim_create_user_command('Baz', function()
+    vim.g.baz_called = true
+end, {})
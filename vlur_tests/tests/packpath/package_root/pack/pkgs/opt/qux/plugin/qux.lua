@@ -0,0 +1 @@
+vim.g.loaded_qux = (vim.g.loaded_qux or 0) + 1
@@ -0,0 +1,86 @@
+local vlur = require 'vlur'
+
+local helper = dofile 'tests/helper.lua'
+local git = helper.git
+
+local data_dir = vim.fn.stdpath 'data'
+local origin = data_dir .. '/update_origin'
+
+vim.fn.delete(data_dir, 'rf')
+vim.fn.mkdir(origin, 'p')
+git(origin, { 'init', '--quiet', '--initial-branch', 'main' })
+git(origin, { 'commit', '--quiet', '--allow-empty', '--message', 'first' })
+git(origin, { 'tag', 'v1' })
+git(origin, { 'tag', 'v1.0.0' })
+
+local messages = helper.capture_notify()
+
+vlur.setup {
+    follow = {
+        path = data_dir .. '/follow',
+        install = vlur.install.git(origin),
+    },
+    pinned = {
+        path = data_dir .. '/pinned',
+        install = vlur.install.git(origin, { tag = 'v1' }),
+    },
+    versioned = {
+        path = data_dir .. '/versioned',
+        install = vlur.install.git(origin, { version = '^1' }),
+    },
+    missing = {
+        path = data_dir .. '/missing',
+        install = vlur.install.git(data_dir .. '/no_such_repository'),
+    },
+}
+assert(#messages == 1, vim.inspect(messages))
+assert(messages[1]:find 'failed to install missing', messages[1])
+messages = helper.capture_notify()
+
+local first = git(origin, { 'rev-parse', '--short', 'HEAD' })
+-- e.g. pinned to a commit before
+git(data_dir .. '/follow', { 'checkout', '--quiet', '--detach' })
+git(origin, { 'commit', '--quiet', '--allow-empty', '--message', 'second' })
+git(origin, { 'tag', 'v1.1.0' })
+local second = git(origin, { 'rev-parse', '--short', 'HEAD' })
+-- not satisfying the version constraint
+vim.fn.mkdir(origin .. '/plugin', 'p')
+vim.fn.writefile({ 'vim.g.loaded_follow = true' }, origin .. '/plugin/follow.lua')
+git(origin, { 'add', 'plugin/follow.lua' })
+git(origin, { 'commit', '--quiet', '--message', 'third' })
+git(origin, { 'tag', 'v2.0.0' })
+local third = git(origin, { 'rev-parse', '--short', 'HEAD' })
+
+local report = vlur.update()
+assert(vim.deep_equal(report, {
+    follow = { old = first, new = third },
+    pinned = { old = first, new = first },
+    versioned = { old = first, new = second },
+}), vim.inspect(report))
+assert(git(data_dir .. '/follow', { 'rev-parse', '--short', 'HEAD' }) == third)
+assert(git(data_dir .. '/follow', { 'branch', '--show-current' }) == 'main')
+assert(git(data_dir .. '/versioned', { 'config', 'vlur.tag' }) == 'v1.1.0')
+assert(#messages == 1, vim.inspect(messages))
+local lines = vim.split(messages[1], '\n')
+table.sort(lines)
+assert(vim.deep_equal(lines, {
+    ('vlur: updated follow: %s -> %s'):format(first, third),
+    ('vlur: updated versioned: %s -> %s'):format(first, second),
+}), messages[1])
+
+-- The cache is read again to find the new file.
+assert(vim.fn.filereadable(vim.fn.stdpath 'cache' .. '/vlur/cache') == 0)
+assert(vim.g.loaded_follow == nil)
+vlur.setup {
+    follow = {
+        path = data_dir .. '/follow',
+        install = vlur.install.git(origin),
+    },
+}
+assert(vim.g.loaded_follow == true)
+
+messages = helper.capture_notify()
+report = vlur.update()
+assert(report.follow.old == third and report.follow.new == third)
+assert(report.versioned.old == second and report.versioned.new == second)
+assert(messages[1] == 'vlur: all plugins are up to date', vim.inspect(messages))
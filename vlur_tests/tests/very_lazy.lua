@@ -0,0 +1,35 @@
+local g = vim.g
+
+local vlur = require 'vlur'
+
+local root = vim.fn.getcwd() .. '/tests/lazy/plugins'
+
+vlur.setup {
+    very_lazy = {
+        path = root .. '/very_lazy',
+        lazy = { vlur.lazy.event('User', 'VeryLazy') },
+    },
+}
+
+assert(g.loaded_very_lazy == nil)
+
+-- must not be fired twice
+vlur.setup {}
+
+local count = 0
+vim.api.nvim_create_autocmd('User', {
+    pattern = 'VeryLazy',
+    callback = function()
+        count = count + 1
+        assert(vim.v.vim_did_enter == 1)
+        assert(g.loaded_very_lazy ~= nil)
+        vim.defer_fn(function()
+            assert(count == 1, count)
+            vim.cmd.source '../scripts/quit.vim'
+        end, 100)
+    end,
+})
+
+vim.defer_fn(function()
+    vim.cmd 'cquit! 1'
+end, 10000)